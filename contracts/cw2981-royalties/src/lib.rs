@@ -5,12 +5,15 @@ pub mod query;
 pub use query::{check_royalties, query_royalties_info};
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_json_binary, Empty};
+use cosmwasm_std::{to_json_binary, Binary, Deps, Empty, Env, StdResult, Storage};
+use cw721::error::Cw721ContractError;
+use cw721::execute::validate_metadata_extension;
 pub use cw721_base::{
     execute::Cw721Execute, msg::InstantiateMsg, query::Cw721Query, Cw721Contract,
 };
 
 use crate::error::ContractError;
+use crate::msg::QueryExtensionMsg;
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cw2981-royalties";
@@ -45,11 +48,79 @@ pub struct Metadata {
     pub royalty_payment_address: Option<String>,
 }
 
+impl From<Metadata> for cw721::state::Metadata {
+    fn from(metadata: Metadata) -> Self {
+        Self {
+            image: metadata.image,
+            image_data: metadata.image_data,
+            external_url: metadata.external_url,
+            description: metadata.description,
+            name: metadata.name,
+            attributes: metadata.attributes.map(|attributes| {
+                attributes
+                    .into_iter()
+                    .map(|attribute| cw721::state::Trait {
+                        display_type: attribute.display_type,
+                        trait_type: attribute.trait_type,
+                        value: attribute.value,
+                    })
+                    .collect()
+            }),
+            background_color: metadata.background_color,
+            animation_url: metadata.animation_url,
+            youtube_url: metadata.youtube_url,
+        }
+    }
+}
+
 pub type Extension = Option<Metadata>;
 
 pub type MintExtension = Option<Extension>;
 
-pub type Cw2981Contract<'a> = Cw721Contract<'a, Extension, Empty, Empty>;
+pub struct Cw2981Contract<'a>(pub Cw721Contract<'a, Extension, Empty, Empty, QueryExtensionMsg>);
+
+impl Default for Cw2981Contract<'static> {
+    fn default() -> Self {
+        Self(Cw721Contract::default())
+    }
+}
+
+impl<'a> Cw721Execute<Extension, Empty, Empty> for Cw2981Contract<'a> {
+    /// Validates the royalty percentage and the OpenSea fields of `Metadata` against the stored rules,
+    /// on mint, voucher redemption and `UpdateNftInfo`
+    fn validate_extension(
+        &self,
+        storage: &dyn Storage,
+        extension: &Extension,
+    ) -> Result<(), Cw721ContractError> {
+        // no need to check < 0 because royalty_percentage is u64
+        if let Some(Metadata {
+            royalty_percentage: Some(royalty_percentage),
+            ..
+        }) = extension
+        {
+            if *royalty_percentage > 100 {
+                return Err(Cw721ContractError::InvalidExtension {
+                    reason: ContractError::InvalidRoyaltyPercentage.to_string(),
+                });
+            }
+        }
+        let metadata = extension.clone().map(cw721::state::Metadata::from);
+        validate_metadata_extension(storage, &metadata)
+    }
+}
+
+impl<'a> Cw721Query<Extension, QueryExtensionMsg> for Cw2981Contract<'a> {
+    fn query_extension(&self, deps: Deps, env: Env, msg: QueryExtensionMsg) -> StdResult<Binary> {
+        match msg {
+            QueryExtensionMsg::RoyaltyInfo {
+                token_id,
+                sale_price,
+            } => to_json_binary(&query_royalties_info(deps, env, token_id, sale_price)?),
+            QueryExtensionMsg::CheckRoyalties {} => to_json_binary(&check_royalties(deps)?),
+        }
+    }
+}
 pub type ExecuteMsg = cw721_base::msg::ExecuteMsg<Extension, Empty>;
 
 #[cfg(not(feature = "library"))]
@@ -59,7 +130,7 @@ pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     #[entry_point]
     pub fn instantiate(
@@ -85,37 +156,21 @@ pub mod entry {
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
-        if let ExecuteMsg::Mint {
-            extension:
-                Some(Metadata {
-                    royalty_percentage: Some(royalty_percentage),
-                    ..
-                }),
-            ..
-        } = &msg
-        {
-            // validate royalty_percentage to be between 0 and 100
-            // no need to check < 0 because royalty_percentage is u64
-            if *royalty_percentage > 100 {
-                return Err(ContractError::InvalidRoyaltyPercentage);
-            }
-        }
-
         Cw2981Contract::default()
             .execute(deps, env, info, msg)
             .map_err(Into::into)
     }
 
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        Cw2981Contract::default()
+            .reply(deps, env, msg)
+            .map_err(Into::into)
+    }
+
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-        match msg {
-            QueryMsg::RoyaltyInfo {
-                token_id,
-                sale_price,
-            } => to_json_binary(&query_royalties_info(deps, env, token_id, sale_price)?),
-            QueryMsg::CheckRoyalties {} => to_json_binary(&check_royalties(deps)?),
-            _ => Cw2981Contract::default().query(deps, env, msg.into()),
-        }
+        Cw2981Contract::default().query(deps, env, msg.into())
     }
 }
 
@@ -123,8 +178,12 @@ pub mod entry {
 mod tests {
     use super::*;
     use crate::msg::{CheckRoyaltiesResponse, QueryMsg, RoyaltiesInfoResponse};
+    use cw721::msg::{Cw721QueryMsg, MintVoucher};
+    use cw721::Expiration;
+    use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+    use sha2::{Digest, Sha256};
 
-    use cosmwasm_std::{from_json, Uint128};
+    use cosmwasm_std::{coin, from_json, to_json_vec, Uint128};
 
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 
@@ -141,6 +200,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: None,
             withdraw_address: None,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -178,6 +240,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: None,
             withdraw_address: None,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -193,9 +258,98 @@ mod tests {
                 ..Metadata::default()
             }),
         };
-        // mint will return StdError
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap_err();
+        let invalid_royalty = ContractError::Base(Cw721ContractError::InvalidExtension {
+            reason: ContractError::InvalidRoyaltyPercentage.to_string(),
+        });
+        assert_eq!(err, invalid_royalty);
+
+        // also checked on voucher redemption
+        let signing_key = SigningKey::from_bytes(&[2u8; 32].into()).unwrap();
+        let pub_key = Binary::from(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes(),
+        );
+        entry::execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::SetVoucherSigner {
+                pub_key: Some(pub_key),
+            },
+        )
+        .unwrap();
+        let env = mock_env();
+        let extension = Some(Metadata {
+            royalty_percentage: Some(101),
+            royalty_payment_address: Some("jeanluc".to_string()),
+            ..Metadata::default()
+        });
+        let voucher = MintVoucher {
+            token_id: token_id.to_string(),
+            token_uri: None,
+            extension_hash: Binary::from(
+                Sha256::digest(to_json_vec(&extension).unwrap()).as_slice(),
+            ),
+            price: coin(0, "uatom"),
+            expires: Expiration::AtHeight(env.block.height + 10),
+            nonce: 1,
+            chain_id: env.block.chain_id.clone(),
+            contract_address: env.contract.address.to_string(),
+        };
+        let hash = Sha256::digest(to_json_vec(&voucher).unwrap());
+        let signature: Signature = signing_key.sign_prehash(&hash).unwrap();
+        let err = entry::execute(
+            deps.as_mut(),
+            env,
+            mock_info("buyer", &[]),
+            ExecuteMsg::RedeemVoucher {
+                voucher,
+                extension,
+                signature: Binary::from(signature.to_bytes().as_slice()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, invalid_royalty);
+    }
+
+    #[test]
+    fn validate_metadata() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "SpaceShips".to_string(),
+            symbol: "SPACE".to_string(),
+            minter: None,
+            withdraw_address: None,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        // OpenSea fields are validated like the default metadata extension
+        let exec_msg = ExecuteMsg::Mint {
+            token_id: "Enterprise".to_string(),
+            owner: "john".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                image: Some("http://starships.example.com/enterprise.png".into()),
+                royalty_percentage: Some(10),
+                ..Metadata::default()
+            }),
+        };
         let err = entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap_err();
-        assert_eq!(err, ContractError::InvalidRoyaltyPercentage);
+        assert_eq!(
+            err,
+            ContractError::Base(Cw721ContractError::UrlSchemeNotAllowed {
+                field: "image".into(),
+                scheme: "http".into()
+            })
+        );
     }
 
     #[test]
@@ -209,6 +363,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: None,
             withdraw_address: None,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -232,7 +389,15 @@ mod tests {
         assert_eq!(res, expected);
 
         // also check the longhand way
-        let query_msg = QueryMsg::CheckRoyalties {};
+        let query_msg = QueryMsg::Royalty(QueryExtensionMsg::CheckRoyalties {});
+        let query_res: CheckRoyaltiesResponse =
+            from_json(entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(query_res, expected);
+
+        // and as cw721 extension query
+        let query_msg = QueryMsg::Base(Cw721QueryMsg::Extension {
+            msg: QueryExtensionMsg::CheckRoyalties {},
+        });
         let query_res: CheckRoyaltiesResponse =
             from_json(entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
         assert_eq!(query_res, expected);
@@ -248,6 +413,9 @@ mod tests {
             symbol: "SPACE".to_string(),
             minter: None,
             withdraw_address: None,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
         };
         let env = mock_env();
         entry::instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
@@ -281,11 +449,27 @@ mod tests {
         .unwrap();
         assert_eq!(res, expected);
 
-        // also check the longhand way
-        let query_msg = QueryMsg::RoyaltyInfo {
-            token_id: token_id.to_string(),
-            sale_price: Uint128::new(100),
-        };
+        // also check the longhand way, as sent by marketplaces
+        let query_msg: QueryMsg =
+            from_json(br#"{"royalty_info":{"token_id":"Enterprise","sale_price":"100"}}"#).unwrap();
+        assert_eq!(
+            query_msg,
+            QueryMsg::Royalty(QueryExtensionMsg::RoyaltyInfo {
+                token_id: token_id.to_string(),
+                sale_price: Uint128::new(100),
+            })
+        );
+        let query_res: RoyaltiesInfoResponse =
+            from_json(entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(query_res, expected);
+
+        // and as cw721 extension query
+        let query_msg = QueryMsg::Base(Cw721QueryMsg::Extension {
+            msg: QueryExtensionMsg::RoyaltyInfo {
+                token_id: token_id.to_string(),
+                sale_price: Uint128::new(100),
+            },
+        });
         let query_res: RoyaltiesInfoResponse =
             from_json(entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
         assert_eq!(query_res, expected);
@@ -1,19 +1,32 @@
 use crate::Extension;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::Uint128;
 use cw721::msg::Cw721QueryMsg;
-use cw721_base::{
-    msg::{
-        AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, MinterResponse, NftInfoResponse,
-        NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
-    },
-    state::CollectionInfo,
-};
-use cw_ownable::Ownership;
 
+/// Accepts the royalty queries at top level, as sent by marketplaces, next to all `cw721` queries.
+/// Royalty queries can also be passed as `Cw721QueryMsg::Extension { msg }`.
 #[cw_serde]
 #[derive(QueryResponses)]
+#[query_responses(nested)]
+#[serde(untagged)]
 pub enum QueryMsg {
+    Royalty(QueryExtensionMsg),
+    Base(Cw721QueryMsg<Extension, QueryExtensionMsg>),
+}
+
+impl From<QueryMsg> for Cw721QueryMsg<Extension, QueryExtensionMsg> {
+    fn from(msg: QueryMsg) -> Self {
+        match msg {
+            QueryMsg::Royalty(msg) => Cw721QueryMsg::Extension { msg },
+            QueryMsg::Base(msg) => msg,
+        }
+    }
+}
+
+/// Royalty queries of CW-2981
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryExtensionMsg {
     /// Should be called on sale to see if royalties are owed
     /// by the marketplace selling the NFT, if CheckRoyalties
     /// returns true
@@ -34,169 +47,6 @@ pub enum QueryMsg {
     /// (i.e. always check on sale)
     #[returns(CheckRoyaltiesResponse)]
     CheckRoyalties {},
-
-    // -- below copied from Cw721QueryMsg
-    /// Return the owner of the given token, error if token does not exist
-    #[returns(OwnerOfResponse)]
-    OwnerOf {
-        token_id: String,
-        /// unset or false will filter out expired approvals, you must set to true to see them
-        include_expired: Option<bool>,
-    },
-    /// Return operator that can access all of the owner's tokens.
-    #[returns(ApprovalResponse)]
-    Approval {
-        token_id: String,
-        spender: String,
-        include_expired: Option<bool>,
-    },
-    /// Return approvals that a token has
-    #[returns(ApprovalsResponse)]
-    Approvals {
-        token_id: String,
-        include_expired: Option<bool>,
-    },
-    /// Return approval of a given operator for all tokens of an owner, error if not set
-    #[returns(OperatorResponse)]
-    Operator {
-        owner: String,
-        operator: String,
-        include_expired: Option<bool>,
-    },
-    /// List all operators that can access all of the owner's tokens
-    #[returns(OperatorsResponse)]
-    AllOperators {
-        owner: String,
-        /// unset or false will filter out expired items, you must set to true to see them
-        include_expired: Option<bool>,
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-    /// Total number of tokens issued
-    #[returns(NumTokensResponse)]
-    NumTokens {},
-
-    #[returns(CollectionInfo)]
-    ContractInfo {},
-
-    /// With MetaData Extension.
-    /// Returns top-level metadata about the contract
-    #[returns(CollectionInfo)]
-    GetCollectionInfo {},
-
-    #[returns(Ownership<Addr>)]
-    Ownership {},
-
-    /// With MetaData Extension.
-    /// Returns metadata about one particular token, based on *ERC721 Metadata JSON Schema*
-    /// but directly from the contract
-    #[returns(NftInfoResponse<Extension>)]
-    NftInfo { token_id: String },
-    /// With MetaData Extension.
-    /// Returns the result of both `NftInfo` and `OwnerOf` as one query as an optimization
-    /// for clients
-    #[returns(AllNftInfoResponse<Extension>)]
-    AllNftInfo {
-        token_id: String,
-        /// unset or false will filter out expired approvals, you must set to true to see them
-        include_expired: Option<bool>,
-    },
-
-    /// With Enumerable extension.
-    /// Returns all tokens owned by the given address, [] if unset.
-    #[returns(TokensResponse)]
-    Tokens {
-        owner: String,
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-    /// With Enumerable extension.
-    /// Requires pagination. Lists all token_ids controlled by the contract.
-    #[returns(TokensResponse)]
-    AllTokens {
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-
-    /// Return the minter
-    #[returns(MinterResponse)]
-    Minter {},
-
-    #[returns(Option<String>)]
-    GetWithdrawAddress {},
-
-    // -- below queries, Extension and GetCollectionInfoExtension, are just dummies, since type annotations are required for
-    // -- TMetadataExtension and TCollectionInfoExtension, Error:
-    // -- "type annotations needed: cannot infer type for type parameter `TMetadataExtension` declared on the enum `Cw721QueryMsg`"
-    #[returns(())]
-    Extension { msg: Extension },
-}
-
-impl From<QueryMsg> for Cw721QueryMsg<Extension> {
-    fn from(msg: QueryMsg) -> Cw721QueryMsg<Extension> {
-        match msg {
-            QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            } => Cw721QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            },
-            QueryMsg::NumTokens {} => Cw721QueryMsg::NumTokens {},
-            QueryMsg::ContractInfo {} => Cw721QueryMsg::ContractInfo {},
-            QueryMsg::NftInfo { token_id } => Cw721QueryMsg::NftInfo { token_id },
-            QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            } => Cw721QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            },
-            QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            } => Cw721QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            },
-            QueryMsg::AllTokens { start_after, limit } => {
-                Cw721QueryMsg::AllTokens { start_after, limit }
-            }
-            #[allow(deprecated)]
-            QueryMsg::Minter {} => Cw721QueryMsg::Minter {},
-            QueryMsg::GetWithdrawAddress {} => Cw721QueryMsg::GetWithdrawAddress {},
-            QueryMsg::AllOperators {
-                owner,
-                include_expired,
-                start_after,
-                limit,
-            } => Cw721QueryMsg::AllOperators {
-                owner,
-                include_expired,
-                start_after,
-                limit,
-            },
-            QueryMsg::Approval {
-                token_id,
-                spender,
-                include_expired,
-            } => Cw721QueryMsg::Approval {
-                token_id,
-                spender,
-                include_expired,
-            },
-            QueryMsg::Approvals {
-                token_id,
-                include_expired,
-            } => Cw721QueryMsg::Approvals {
-                token_id,
-                include_expired,
-            },
-            msg => unreachable!("Unsupported query: {:?}", msg),
-        }
-    }
 }
 
 #[cw_serde]
@@ -26,7 +26,7 @@ pub mod entry {
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
     use cw721::{
         error::Cw721ContractError,
         execute::Cw721Execute,
@@ -43,7 +43,8 @@ pub mod entry {
         info: MessageInfo,
         msg: Cw721InstantiateMsg,
     ) -> Result<Response, Cw721ContractError> {
-        let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        let contract =
+            Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
         contract.instantiate(deps, env, info, msg, CONTRACT_NAME, CONTRACT_VERSION)
     }
 
@@ -54,17 +55,26 @@ pub mod entry {
         info: MessageInfo,
         msg: Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>,
     ) -> Result<Response, Cw721ContractError> {
-        let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        let contract =
+            Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
         contract.execute(deps, env, info, msg)
     }
 
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, Cw721ContractError> {
+        let contract =
+            Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+        contract.reply(deps, env, msg)
+    }
+
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn query(
         deps: Deps,
         env: Env,
-        msg: Cw721QueryMsg<DefaultOptionMetadataExtension>,
+        msg: Cw721QueryMsg<DefaultOptionMetadataExtension, Empty>,
     ) -> StdResult<Binary> {
-        let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        let contract =
+            Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
         contract.query(deps, env, msg)
     }
 
@@ -74,7 +84,8 @@ pub mod entry {
         env: Env,
         msg: Cw721MigrateMsg,
     ) -> Result<Response, Cw721ContractError> {
-        let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        let contract =
+            Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
         contract.migrate(deps, env, msg, CONTRACT_NAME, CONTRACT_VERSION)
     }
 }
@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use cosmwasm_std::CustomMsg;
 
 // expose to all others using contract, so others dont need to import cw721
@@ -14,15 +16,24 @@ pub struct Cw721Contract<
     TCustomResponseMessage,
     // Message passed for updating metadata.
     TMetadataExtensionMsg,
+    // Message passed for custom queries, handled by `Cw721Query::query_extension`.
+    TQueryExtensionMsg,
 > where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
 {
     pub config: Cw721Config<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>,
+    pub(crate) _query_extension: PhantomData<TQueryExtensionMsg>,
 }
 
-impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg> Default
-    for Cw721Contract<'static, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg> Default
+    for Cw721Contract<
+        'static,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
@@ -30,6 +41,7 @@ where
     fn default() -> Self {
         Self {
             config: Cw721Config::default(),
+            _query_extension: PhantomData,
         }
     }
 }
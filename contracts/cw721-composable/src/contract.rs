@@ -0,0 +1,338 @@
+use std::marker::PhantomData;
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply,
+    Response, StdResult,
+};
+use cw721::error::Cw721ContractError;
+use cw721::helpers::Cw721Contract as Cw721Helper;
+use cw721::msg::{AddressesResponse, Cw721ExecuteMsg};
+use cw721::receiver::Cw721ReceiveMsg;
+use cw721::state::{Cw721Config, DefaultOptionMetadataExtension, MINTER};
+use cw721_base::execute::Cw721Execute;
+use cw721_base::query::Cw721Query;
+use cw721_base::Cw721Contract;
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::msg::{
+    BaseExecuteMsg, ChildToken, ChildTokensResponse, ComposableExecuteMsg, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, QueryExtensionMsg, QueryMsg, ReceiveMsg, RootOwnerOfResponse,
+};
+use crate::state::{
+    clear_children, detach_from_parent, has_children, is_child_collection, root_owner, CHILDREN,
+    CHILD_COLLECTIONS, PARENTS,
+};
+
+// Version info for migration
+const CONTRACT_NAME: &str = "crates.io:cw721-composable";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+pub struct ComposableContract<'a>(
+    pub Cw721Contract<'a, DefaultOptionMetadataExtension, Empty, Empty, QueryExtensionMsg>,
+);
+
+impl Default for ComposableContract<'static> {
+    fn default() -> Self {
+        Self(Cw721Contract::default())
+    }
+}
+
+impl<'a> Cw721Execute<DefaultOptionMetadataExtension, Empty, Empty> for ComposableContract<'a> {}
+
+impl<'a> Cw721Query<DefaultOptionMetadataExtension, QueryExtensionMsg> for ComposableContract<'a> {
+    fn query_extension(&self, deps: Deps, env: Env, msg: QueryExtensionMsg) -> StdResult<Binary> {
+        match msg {
+            QueryExtensionMsg::ChildTokens {
+                token_id,
+                start_after,
+                limit,
+            } => to_json_binary(&query_child_tokens(deps, token_id, start_after, limit)?),
+            QueryExtensionMsg::RootOwnerOf { contract, token_id } => {
+                to_json_binary(&query_root_owner_of(deps, env, contract, token_id)?)
+            }
+            QueryExtensionMsg::ChildCollections { start_after, limit } => {
+                to_json_binary(&query_child_collections(deps, start_after, limit)?)
+            }
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    Ok(ComposableContract::default().instantiate(
+        deps,
+        env,
+        info,
+        msg,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Composable(ComposableExecuteMsg::ReceiveNft(receive_msg)) => {
+            execute_receive_child(deps, env, info, receive_msg)
+        }
+        ExecuteMsg::Composable(ComposableExecuteMsg::DetachChild {
+            token_id,
+            child_contract,
+            child_token_id,
+            recipient,
+        }) => execute_detach_child(
+            deps,
+            env,
+            info,
+            token_id,
+            child_contract,
+            child_token_id,
+            recipient,
+        ),
+        ExecuteMsg::Composable(ComposableExecuteMsg::UpdateChildCollections { add, remove }) => {
+            execute_update_child_collections(deps, info, add, remove)
+        }
+        ExecuteMsg::Base(msg) => {
+            // children are held by this contract, burning their parent would lock them
+            if let BaseExecuteMsg::Burn { token_id } | BaseExecuteMsg::ForceBurn { token_id, .. } =
+                &msg
+            {
+                if has_children(deps.storage, &env.contract.address, token_id)? {
+                    return Err(ContractError::HasChildren {
+                        token_id: token_id.clone(),
+                    });
+                }
+                clear_children(deps.storage, token_id)?;
+            }
+            // forced moves of a nested token of this collection detach it from its parent
+            if let BaseExecuteMsg::ForceTransfer { token_id, .. }
+            | BaseExecuteMsg::ForceBurn { token_id, .. } = &msg
+            {
+                detach_from_parent(deps.storage, &env.contract.address, token_id)?;
+            }
+            Ok(ComposableContract::default().execute(deps, env, info, msg)?)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    Ok(ComposableContract::default().reply(deps, env, msg)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    ComposableContract::default().query(deps, env, msg.into())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(ComposableContract::default().migrate(deps, env, msg, CONTRACT_NAME, CONTRACT_VERSION)?)
+}
+
+/// Attaches a token sent via `SendNft` to the parent token given in `msg`.
+/// The sending collection is `info.sender` and is trusted to report the sender and its owner
+/// correctly, so only tokens of this or an allowed collection are accepted.
+pub fn execute_receive_child(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    if !is_child_collection(deps.storage, &env.contract.address, &info.sender) {
+        return Err(ContractError::CollectionNotAllowed {
+            contract: info.sender.to_string(),
+        });
+    }
+    let ReceiveMsg { token_id } = from_json(&receive_msg.msg)?;
+    let config = Cw721Config::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    // only the direct owner may attach, this also prevents cycles within this collection
+    let parent = config.nft_info.load(deps.storage, &token_id)?;
+    if parent.owner.as_str() != receive_msg.sender {
+        return Err(ContractError::NotParentOwner {});
+    }
+
+    let child_contract = info.sender;
+    let child_owner = if child_contract == env.contract.address {
+        config
+            .nft_info
+            .load(deps.storage, &receive_msg.token_id)?
+            .owner
+    } else {
+        let child_response =
+            Cw721Helper::<Empty, Empty>(child_contract.clone(), PhantomData, PhantomData)
+                .owner_of(&deps.querier, &receive_msg.token_id, false)?;
+        Addr::unchecked(child_response.owner)
+    };
+    if child_owner != env.contract.address {
+        return Err(ContractError::ChildNotReceived {});
+    }
+
+    CHILDREN.save(
+        deps.storage,
+        (&token_id, &child_contract, &receive_msg.token_id),
+        &Empty {},
+    )?;
+    PARENTS.save(
+        deps.storage,
+        (&child_contract, &receive_msg.token_id),
+        &token_id,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "receive_child")
+        .add_attribute("token_id", token_id)
+        .add_attribute("child_contract", child_contract)
+        .add_attribute("child_token_id", receive_msg.token_id))
+}
+
+pub fn execute_detach_child(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    child_contract: String,
+    child_token_id: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let child_contract = deps.api.addr_validate(&child_contract)?;
+    let parent = PARENTS.may_load(deps.storage, (&child_contract, &child_token_id))?;
+    if parent.as_ref() != Some(&token_id) {
+        return Err(ContractError::ChildNotFound {});
+    }
+    let (_, owner) = root_owner(deps.storage, &env.contract.address, &token_id)?;
+    if owner != info.sender {
+        return Err(ContractError::NotRootOwner {});
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    CHILDREN.remove(deps.storage, (&token_id, &child_contract, &child_token_id));
+    PARENTS.remove(deps.storage, (&child_contract, &child_token_id));
+
+    let transfer_msg =
+        Cw721Helper::<Empty, Empty>(child_contract.clone(), PhantomData, PhantomData).call(
+            Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: child_token_id.clone(),
+            },
+        )?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attribute("action", "detach_child")
+        .add_attribute("token_id", token_id)
+        .add_attribute("child_contract", child_contract)
+        .add_attribute("child_token_id", child_token_id)
+        .add_attribute("recipient", recipient))
+}
+
+pub fn execute_update_child_collections(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    MINTER
+        .assert_owner(deps.storage, &info.sender)
+        .map_err(Cw721ContractError::from)?;
+    for contract in &add {
+        let contract = deps.api.addr_validate(contract)?;
+        CHILD_COLLECTIONS.save(deps.storage, &contract, &Empty {})?;
+    }
+    for contract in &remove {
+        let contract = deps.api.addr_validate(contract)?;
+        CHILD_COLLECTIONS.remove(deps.storage, &contract);
+    }
+    Ok(Response::new()
+        .add_attribute("action", "update_child_collections")
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(",")))
+}
+
+pub fn query_child_tokens(
+    deps: Deps,
+    token_id: String,
+    start_after: Option<ChildToken>,
+    limit: Option<u32>,
+) -> StdResult<ChildTokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|child| -> StdResult<_> {
+            Ok((deps.api.addr_validate(&child.contract)?, child.token_id))
+        })
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(contract, token_id)| Bound::exclusive((contract, token_id.as_str())));
+
+    let children = CHILDREN
+        .prefix(&token_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(contract, token_id)| ChildToken {
+                contract: contract.to_string(),
+                token_id,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ChildTokensResponse { children })
+}
+
+pub fn query_root_owner_of(
+    deps: Deps,
+    env: Env,
+    contract: Option<String>,
+    token_id: String,
+) -> StdResult<RootOwnerOfResponse> {
+    let contract = contract
+        .map(|contract| deps.api.addr_validate(&contract))
+        .transpose()?;
+    // tokens of other collections are resolved through their parent in this collection
+    let token_id = match contract {
+        Some(contract) if contract != env.contract.address => {
+            PARENTS.load(deps.storage, (&contract, &token_id))?
+        }
+        _ => token_id,
+    };
+    let (root_token_id, root_owner) = root_owner(deps.storage, &env.contract.address, &token_id)?;
+    Ok(RootOwnerOfResponse {
+        root_owner: root_owner.to_string(),
+        root_token_id,
+    })
+}
+
+pub fn query_child_collections(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AddressesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|contract| deps.api.addr_validate(&contract))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let addresses = CHILD_COLLECTIONS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(String::from))
+        .collect::<StdResult<_>>()?;
+    Ok(AddressesResponse { addresses })
+}
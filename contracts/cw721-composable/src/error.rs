@@ -0,0 +1,30 @@
+use cosmwasm_std::StdError;
+use cw721_base::error::ContractError as Cw721ContractError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error(transparent)]
+    Base(#[from] Cw721ContractError),
+
+    #[error("Caller is not the owner of the parent token")]
+    NotParentOwner {},
+
+    #[error("Caller is not the root owner of the parent token")]
+    NotRootOwner {},
+
+    #[error("Child token has not been transferred to this contract")]
+    ChildNotReceived {},
+
+    #[error("Child token is not attached to the parent token")]
+    ChildNotFound {},
+
+    #[error("Tokens of collection {contract} can't be attached as child tokens")]
+    CollectionNotAllowed { contract: String },
+
+    #[error("Token {token_id} still has child tokens attached")]
+    HasChildren { token_id: String },
+}
@@ -0,0 +1,112 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Empty;
+use cw721::msg::{AddressesResponse, Cw721ExecuteMsg, Cw721QueryMsg};
+use cw721::receiver::Cw721ReceiveMsg;
+use cw721::state::DefaultOptionMetadataExtension;
+
+pub use cw721::msg::{Cw721InstantiateMsg as InstantiateMsg, Cw721MigrateMsg as MigrateMsg};
+
+pub type BaseExecuteMsg = Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>;
+pub type BaseQueryMsg = Cw721QueryMsg<DefaultOptionMetadataExtension, QueryExtensionMsg>;
+
+/// Accepts composable messages next to all `cw721` execute messages
+#[cw_serde]
+#[serde(untagged)]
+pub enum ExecuteMsg {
+    Composable(ComposableExecuteMsg),
+    Base(BaseExecuteMsg),
+}
+
+/// Accepts composable queries at top level next to all `cw721` queries.
+/// Composable queries can also be passed as `Cw721QueryMsg::Extension { msg }`.
+#[cw_serde]
+#[derive(QueryResponses)]
+#[query_responses(nested)]
+#[serde(untagged)]
+pub enum QueryMsg {
+    Composable(QueryExtensionMsg),
+    Base(BaseQueryMsg),
+}
+
+impl From<QueryMsg> for BaseQueryMsg {
+    fn from(msg: QueryMsg) -> Self {
+        match msg {
+            QueryMsg::Composable(msg) => Cw721QueryMsg::Extension { msg },
+            QueryMsg::Base(msg) => msg,
+        }
+    }
+}
+
+#[cw_serde]
+pub enum ComposableExecuteMsg {
+    /// Attaches the received cw721 token as a child of the parent token given in `msg`,
+    /// see `ReceiveMsg`. The token must be sent by the owner of the parent token, from this
+    /// or an allowed collection.
+    ReceiveNft(Cw721ReceiveMsg),
+    /// Transfers a child token out of the parent token. Only the root owner of the parent
+    /// token can call this.
+    DetachChild {
+        token_id: String,
+        child_contract: String,
+        child_token_id: String,
+        recipient: String,
+    },
+    /// Updates the collections whose tokens can be attached as children, tokens of this
+    /// collection can always be attached. Children of removed collections no longer block burning
+    /// their parent. Only owner can call this.
+    UpdateChildCollections {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+/// Payload of `Cw721ReceiveMsg.msg`
+#[cw_serde]
+pub struct ReceiveMsg {
+    /// Parent token in this collection receiving the child token
+    pub token_id: String,
+}
+
+/// Composable queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryExtensionMsg {
+    /// Child tokens directly attached to the given token
+    #[returns(ChildTokensResponse)]
+    ChildTokens {
+        token_id: String,
+        start_after: Option<ChildToken>,
+        limit: Option<u32>,
+    },
+    /// Owner at the top of the tree the token belongs to. `contract` defaults to this
+    /// collection, any other collection must be attached as a child token.
+    #[returns(RootOwnerOfResponse)]
+    RootOwnerOf {
+        contract: Option<String>,
+        token_id: String,
+    },
+    /// Collections other than this one whose tokens can be attached as children
+    #[returns(AddressesResponse)]
+    ChildCollections {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct ChildToken {
+    pub contract: String,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct ChildTokensResponse {
+    pub children: Vec<ChildToken>,
+}
+
+#[cw_serde]
+pub struct RootOwnerOfResponse {
+    pub root_owner: String,
+    /// Top-level token in this collection
+    pub root_token_id: String,
+}
@@ -0,0 +1,75 @@
+use cosmwasm_std::{Addr, Empty, Order, StdResult, Storage};
+use cw721::state::{Cw721Config, DefaultOptionMetadataExtension};
+use cw_storage_plus::Map;
+
+/// Child tokens by (parent token id, child contract, child token id)
+pub const CHILDREN: Map<(&str, &Addr, &str), Empty> = Map::new("children");
+/// Parent token id by (child contract, child token id)
+pub const PARENTS: Map<(&Addr, &str), String> = Map::new("parents");
+/// Collections other than this one allowed to attach child tokens
+pub const CHILD_COLLECTIONS: Map<&Addr, Empty> = Map::new("child_collections");
+
+/// Whether tokens of `child_contract` can be attached to tokens of this collection at `contract`
+pub fn is_child_collection(storage: &dyn Storage, contract: &Addr, child_contract: &Addr) -> bool {
+    child_contract == contract || CHILD_COLLECTIONS.has(storage, child_contract)
+}
+
+/// Whether the token has children of this or an allowed collection attached
+pub fn has_children(storage: &dyn Storage, contract: &Addr, token_id: &str) -> StdResult<bool> {
+    for child in CHILDREN
+        .prefix(token_id)
+        .keys(storage, None, None, Order::Ascending)
+    {
+        let (child_contract, _) = child?;
+        if is_child_collection(storage, contract, &child_contract) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Removes all children of the token, e.g. the untrusted ones left on burn
+pub fn clear_children(storage: &mut dyn Storage, token_id: &str) -> StdResult<()> {
+    let children = CHILDREN
+        .prefix(token_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (child_contract, child_token_id) in children {
+        CHILDREN.remove(storage, (token_id, &child_contract, &child_token_id));
+        PARENTS.remove(storage, (&child_contract, &child_token_id));
+    }
+    Ok(())
+}
+
+/// Removes a nested token of this collection from its parent, if attached
+pub fn detach_from_parent(
+    storage: &mut dyn Storage,
+    contract: &Addr,
+    token_id: &str,
+) -> StdResult<()> {
+    if let Some(parent) = PARENTS.may_load(storage, (contract, token_id))? {
+        CHILDREN.remove(storage, (&parent, contract, token_id));
+        PARENTS.remove(storage, (contract, token_id));
+    }
+    Ok(())
+}
+
+/// Walks up the tree of `token_id` in this collection, returning the top-level token and its owner
+pub fn root_owner(
+    storage: &dyn Storage,
+    contract: &Addr,
+    token_id: &str,
+) -> StdResult<(String, Addr)> {
+    let config = Cw721Config::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    let mut token_id = token_id.to_string();
+    loop {
+        let owner = config.nft_info.load(storage, &token_id)?.owner;
+        if &owner != contract {
+            return Ok((token_id, owner));
+        }
+        match PARENTS.may_load(storage, (contract, &token_id))? {
+            Some(parent) => token_id = parent,
+            None => return Ok((token_id, owner)),
+        }
+    }
+}
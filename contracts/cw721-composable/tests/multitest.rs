@@ -0,0 +1,600 @@
+use cosmwasm_std::{to_json_binary, Addr, Empty};
+use cw721::error::Cw721ContractError;
+use cw721::msg::{
+    AddressesResponse, Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg, OwnerOfResponse,
+};
+use cw721::state::DefaultOptionMetadataExtension;
+use cw721_base::OwnershipError;
+use cw721_composable::msg::{
+    BaseExecuteMsg, BaseQueryMsg, ChildToken, ChildTokensResponse, ComposableExecuteMsg,
+    ExecuteMsg, QueryExtensionMsg, QueryMsg, ReceiveMsg, RootOwnerOfResponse,
+};
+use cw721_composable::ContractError;
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+type NftExecuteMsg = Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>;
+
+#[test]
+fn test_attach_and_detach_child() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+    let other = app.api().addr_make("other");
+
+    let Contracts {
+        composable_contract,
+        child_contract,
+    } = setup_contracts(&mut app, admin, owner.clone());
+
+    // only the owner of the parent token can attach children
+    app.execute_contract(
+        owner.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Base(BaseExecuteMsg::TransferNft {
+            recipient: other.to_string(),
+            token_id: "other".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = app
+        .execute_contract(
+            owner.clone(),
+            child_contract.clone(),
+            &NftExecuteMsg::SendNft {
+                contract: composable_contract.to_string(),
+                token_id: "child".to_string(),
+                msg: to_json_binary(&ReceiveMsg {
+                    token_id: "other".to_string(),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotParentOwner {});
+
+    attach(
+        &mut app,
+        &owner,
+        &child_contract,
+        &composable_contract,
+        "child",
+        "parent",
+    );
+    assert_eq!(
+        query_owner(&app, &child_contract, "child"),
+        composable_contract
+    );
+    let res: ChildTokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &composable_contract,
+            &QueryMsg::Composable(QueryExtensionMsg::ChildTokens {
+                token_id: "parent".to_string(),
+                start_after: None,
+                limit: None,
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        res.children,
+        vec![ChildToken {
+            contract: child_contract.to_string(),
+            token_id: "child".to_string(),
+        }]
+    );
+    assert_eq!(
+        query_root_owner(&app, &composable_contract, Some(&child_contract), "child"),
+        RootOwnerOfResponse {
+            root_owner: owner.to_string(),
+            root_token_id: "parent".to_string(),
+        }
+    );
+
+    // transferring the parent moves the child along
+    app.execute_contract(
+        owner.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Base(BaseExecuteMsg::TransferNft {
+            recipient: other.to_string(),
+            token_id: "parent".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_root_owner(&app, &composable_contract, Some(&child_contract), "child").root_owner,
+        other.to_string()
+    );
+
+    // parent with children can't be burned
+    let err: ContractError = app
+        .execute_contract(
+            other.clone(),
+            composable_contract.clone(),
+            &ExecuteMsg::Base(BaseExecuteMsg::Burn {
+                token_id: "parent".to_string(),
+            }),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::HasChildren {
+            token_id: "parent".to_string()
+        }
+    );
+
+    // previous owner can't detach
+    let detach_msg = ExecuteMsg::Composable(ComposableExecuteMsg::DetachChild {
+        token_id: "parent".to_string(),
+        child_contract: child_contract.to_string(),
+        child_token_id: "child".to_string(),
+        recipient: owner.to_string(),
+    });
+    let err: ContractError = app
+        .execute_contract(owner.clone(), composable_contract.clone(), &detach_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotRootOwner {});
+
+    // new owner detaches the child
+    app.execute_contract(other.clone(), composable_contract.clone(), &detach_msg, &[])
+        .unwrap();
+    assert_eq!(query_owner(&app, &child_contract, "child"), owner);
+    let res: ChildTokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &composable_contract,
+            &QueryMsg::Composable(QueryExtensionMsg::ChildTokens {
+                token_id: "parent".to_string(),
+                start_after: None,
+                limit: None,
+            }),
+        )
+        .unwrap();
+    assert!(res.children.is_empty());
+
+    // detaching again fails
+    let err: ContractError = app
+        .execute_contract(other.clone(), composable_contract.clone(), &detach_msg, &[])
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::ChildNotFound {});
+
+    // without children, parent can be burned
+    app.execute_contract(
+        other,
+        composable_contract,
+        &ExecuteMsg::Base(BaseExecuteMsg::Burn {
+            token_id: "parent".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_nested_tokens() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+
+    let Contracts {
+        composable_contract,
+        child_contract,
+    } = setup_contracts(&mut app, admin, owner.clone());
+
+    // parent is attached to another token of the same collection, child to the parent
+    attach(
+        &mut app,
+        &owner,
+        &composable_contract,
+        &composable_contract,
+        "parent",
+        "other",
+    );
+    attach(
+        &mut app,
+        &owner,
+        &child_contract,
+        &composable_contract,
+        "child",
+        "parent",
+    );
+    assert_eq!(
+        query_root_owner(&app, &composable_contract, None, "parent"),
+        RootOwnerOfResponse {
+            root_owner: owner.to_string(),
+            root_token_id: "other".to_string(),
+        }
+    );
+    assert_eq!(
+        query_root_owner(&app, &composable_contract, Some(&child_contract), "child"),
+        RootOwnerOfResponse {
+            root_owner: owner.to_string(),
+            root_token_id: "other".to_string(),
+        }
+    );
+
+    // nested tokens can't be attached to each other, as the parent is owned by the contract
+    let err: ContractError = app
+        .execute_contract(
+            owner.clone(),
+            composable_contract.clone(),
+            &ExecuteMsg::Base(BaseExecuteMsg::SendNft {
+                contract: composable_contract.to_string(),
+                token_id: "other".to_string(),
+                msg: to_json_binary(&ReceiveMsg {
+                    token_id: "parent".to_string(),
+                })
+                .unwrap(),
+            }),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotParentOwner {});
+
+    // root owner detaches the grandchild
+    app.execute_contract(
+        owner.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Composable(ComposableExecuteMsg::DetachChild {
+            token_id: "parent".to_string(),
+            child_contract: child_contract.to_string(),
+            child_token_id: "child".to_string(),
+            recipient: owner.to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &child_contract, "child"), owner);
+
+    // and the nested token of the same collection
+    app.execute_contract(
+        owner.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Composable(ComposableExecuteMsg::DetachChild {
+            token_id: "other".to_string(),
+            child_contract: composable_contract.to_string(),
+            child_token_id: "parent".to_string(),
+            recipient: owner.to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &composable_contract, "parent"), owner);
+}
+
+#[test]
+fn test_child_collections() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+
+    let Contracts {
+        composable_contract,
+        child_contract,
+    } = setup_contracts(&mut app, admin.clone(), owner.clone());
+
+    // only owner can update child collections
+    let update_msg = |add: Vec<&Addr>, remove: Vec<&Addr>| {
+        ExecuteMsg::Composable(ComposableExecuteMsg::UpdateChildCollections {
+            add: add.into_iter().map(ToString::to_string).collect(),
+            remove: remove.into_iter().map(ToString::to_string).collect(),
+        })
+    };
+    let err: ContractError = app
+        .execute_contract(
+            owner.clone(),
+            composable_contract.clone(),
+            &update_msg(vec![&owner], vec![]),
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Base(Cw721ContractError::Ownership(OwnershipError::NotOwner))
+    );
+    let res: AddressesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &composable_contract,
+            &QueryMsg::Composable(QueryExtensionMsg::ChildCollections {
+                start_after: None,
+                limit: None,
+            }),
+        )
+        .unwrap();
+    assert_eq!(res.addresses, vec![child_contract.to_string()]);
+
+    attach(
+        &mut app,
+        &owner,
+        &child_contract,
+        &composable_contract,
+        "child",
+        "parent",
+    );
+
+    // tokens of a removed collection can't be attached
+    app.execute_contract(
+        admin.clone(),
+        composable_contract.clone(),
+        &update_msg(vec![], vec![&child_contract]),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        admin.clone(),
+        child_contract.clone(),
+        &NftExecuteMsg::Mint {
+            token_id: "child2".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = app
+        .execute_contract(
+            owner.clone(),
+            child_contract.clone(),
+            &NftExecuteMsg::SendNft {
+                contract: composable_contract.to_string(),
+                token_id: "child2".to_string(),
+                msg: to_json_binary(&ReceiveMsg {
+                    token_id: "parent".to_string(),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::CollectionNotAllowed {
+            contract: child_contract.to_string()
+        }
+    );
+
+    // children of a removed collection no longer block burning, and are cleared with the parent
+    app.execute_contract(
+        owner.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Base(BaseExecuteMsg::Burn {
+            token_id: "parent".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    let res: ChildTokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &composable_contract,
+            &BaseQueryMsg::Extension {
+                msg: QueryExtensionMsg::ChildTokens {
+                    token_id: "parent".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            },
+        )
+        .unwrap();
+    assert!(res.children.is_empty());
+}
+
+#[test]
+fn test_force_transfer_nested_token() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+
+    let Contracts {
+        composable_contract,
+        ..
+    } = setup_contracts(&mut app, admin.clone(), owner.clone());
+
+    attach(
+        &mut app,
+        &owner,
+        &composable_contract,
+        &composable_contract,
+        "parent",
+        "other",
+    );
+
+    // clawback of the nested token detaches it from its parent
+    app.execute_contract(
+        admin,
+        composable_contract.clone(),
+        &ExecuteMsg::Base(BaseExecuteMsg::ForceTransfer {
+            token_id: "parent".to_string(),
+            recipient: owner.to_string(),
+            reason: "dispute".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &composable_contract, "parent"), owner);
+    let res: ChildTokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &composable_contract,
+            &QueryMsg::Composable(QueryExtensionMsg::ChildTokens {
+                token_id: "other".to_string(),
+                start_after: None,
+                limit: None,
+            }),
+        )
+        .unwrap();
+    assert!(res.children.is_empty());
+    assert_eq!(
+        query_root_owner(&app, &composable_contract, None, "parent"),
+        RootOwnerOfResponse {
+            root_owner: owner.to_string(),
+            root_token_id: "parent".to_string(),
+        }
+    );
+
+    // former parent can be burned
+    app.execute_contract(
+        owner,
+        composable_contract,
+        &ExecuteMsg::Base(BaseExecuteMsg::Burn {
+            token_id: "other".to_string(),
+        }),
+        &[],
+    )
+    .unwrap();
+}
+
+struct Contracts {
+    composable_contract: Addr,
+    child_contract: Addr,
+}
+
+/// Setup the cw721-composable with clawback enabled and an allowed cw721-base child collection,
+/// minting tokens "parent" and "other" in the former and "child" in the latter
+fn setup_contracts(app: &mut App, admin: Addr, owner: Addr) -> Contracts {
+    use cw721_composable::contract::*;
+
+    let composable_code_id = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+    ));
+    let child_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        )
+        .with_reply(cw721_base::entry::reply),
+    ));
+
+    let mut contracts = vec![];
+    for (code_id, label) in [(composable_code_id, "composable"), (child_code_id, "child")] {
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                admin.clone(),
+                &Cw721InstantiateMsg {
+                    name: label.to_string(),
+                    symbol: label.to_uppercase(),
+                    minter: Some(admin.to_string()),
+                    withdraw_address: None,
+                    snapshot_ownership: None,
+                    clawback_enabled: Some(label == "composable"),
+                    clear_user_on_transfer: None,
+                },
+                &[],
+                label.to_string(),
+                None,
+            )
+            .unwrap();
+        contracts.push(contract);
+    }
+    let (composable_contract, child_contract) = (contracts[0].clone(), contracts[1].clone());
+    app.execute_contract(
+        admin.clone(),
+        composable_contract.clone(),
+        &ExecuteMsg::Composable(ComposableExecuteMsg::UpdateChildCollections {
+            add: vec![child_contract.to_string()],
+            remove: vec![],
+        }),
+        &[],
+    )
+    .unwrap();
+
+    for (contract, token_id) in [
+        (&composable_contract, "parent"),
+        (&composable_contract, "other"),
+        (&child_contract, "child"),
+    ] {
+        app.execute_contract(
+            admin.clone(),
+            contract.clone(),
+            &NftExecuteMsg::Mint {
+                token_id: token_id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    Contracts {
+        composable_contract,
+        child_contract,
+    }
+}
+
+fn attach(
+    app: &mut App,
+    owner: &Addr,
+    child_contract: &Addr,
+    composable_contract: &Addr,
+    child_token_id: &str,
+    token_id: &str,
+) {
+    app.execute_contract(
+        owner.clone(),
+        child_contract.clone(),
+        &NftExecuteMsg::SendNft {
+            contract: composable_contract.to_string(),
+            token_id: child_token_id.to_string(),
+            msg: to_json_binary(&ReceiveMsg {
+                token_id: token_id.to_string(),
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn query_owner(app: &App, nft_contract: &Addr, token_id: &str) -> Addr {
+    let res: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            nft_contract,
+            &Cw721QueryMsg::<DefaultOptionMetadataExtension, Empty>::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    Addr::unchecked(res.owner)
+}
+
+fn query_root_owner(
+    app: &App,
+    composable_contract: &Addr,
+    contract: Option<&Addr>,
+    token_id: &str,
+) -> RootOwnerOfResponse {
+    app.wrap()
+        .query_wasm_smart(
+            composable_contract,
+            &QueryMsg::Composable(QueryExtensionMsg::RootOwnerOf {
+                contract: contract.map(ToString::to_string),
+                token_id: token_id.to_string(),
+            }),
+        )
+        .unwrap()
+}
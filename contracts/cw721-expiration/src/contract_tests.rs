@@ -3,18 +3,21 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 
 use cosmwasm_std::{
-    from_json, to_json_binary, Addr, CosmosMsg, DepsMut, Empty, Response, StdError, WasmMsg,
+    coin, from_json, to_json_binary, to_json_vec, Addr, Binary, CosmosMsg, DepsMut, Empty,
+    Response, StdError, WasmMsg,
 };
 
 use cw721::error::Cw721ContractError;
 use cw721::msg::{
-    ApprovalResponse, Cw721ExecuteMsg, NftInfoResponse, OperatorResponse, OperatorsResponse,
-    OwnerOfResponse, TokensResponse,
+    ApprovalResponse, Cw721ExecuteMsg, MintVoucher, NftInfoResponse, OperatorResponse,
+    OperatorsResponse, OwnerOfResponse, PermitPayload, TokensResponse,
 };
 use cw721::receiver::Cw721ReceiveMsg;
-use cw721::state::{CollectionInfo, MINTER};
+use cw721::state::{CollectionInfo, Metadata, MINTER};
 use cw721::{query::Cw721Query, Approval, Expiration};
 use cw_ownable::{Action, Ownership, OwnershipError};
+use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+use sha2::{Digest, Sha256};
 
 use crate::state::Cw721ExpirationContract;
 use crate::{
@@ -29,15 +32,16 @@ const SYMBOL: &str = "MGK";
 fn setup_contract(
     deps: DepsMut<'_>,
     expiration_days: u16,
-) -> Cw721ExpirationContract<'static, DefaultOptionMetadataExtension, Empty, Empty> {
+) -> Cw721ExpirationContract<'static, DefaultOptionMetadataExtension, Empty, Empty, Empty> {
     let contract =
-        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
     let msg = InstantiateMsg {
         expiration_days,
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: None,
+        snapshot_ownership: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -49,7 +53,7 @@ fn setup_contract(
 fn proper_instantiation() {
     let mut deps = mock_dependencies();
     let contract =
-        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
 
     let msg = InstantiateMsg {
         expiration_days: 1,
@@ -57,6 +61,7 @@ fn proper_instantiation() {
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: Some(String::from(CREATOR_ADDR)),
+        snapshot_ownership: None,
     };
     let info = mock_info("creator", &[]);
     let env = mock_env();
@@ -107,7 +112,7 @@ fn proper_instantiation() {
 fn proper_instantiation_with_collection_info() {
     let mut deps = mock_dependencies();
     let contract =
-        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+        Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
 
     let msg = InstantiateMsg {
         expiration_days: 1,
@@ -115,6 +120,7 @@ fn proper_instantiation_with_collection_info() {
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: Some(String::from(CREATOR_ADDR)),
+        snapshot_ownership: None,
     };
     let info = mock_info("creator", &[]);
     let env = mock_env();
@@ -799,6 +805,80 @@ fn test_approve_revoke() {
     );
 }
 
+#[test]
+fn test_token_messages_of_expired_nft() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut(), 1);
+
+    let token_id = "grow".to_string();
+    let mint_msg = Cw721ExecuteMsg::Mint {
+        token_id: token_id.clone(),
+        owner: String::from("owner"),
+        token_uri: None,
+        extension: None,
+    };
+    let mut env = mock_env();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER_ADDR, &[]),
+            mint_msg,
+        )
+        .unwrap();
+
+    let mint_date = env.block.time;
+    let expiration = env.block.time.plus_days(1);
+    env.block.time = expiration;
+    let msgs: Vec<Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>> = vec![
+        Cw721ExecuteMsg::SetUser {
+            token_id: token_id.clone(),
+            user: Some(String::from("user")),
+            expires: None,
+        },
+        Cw721ExecuteMsg::ForceTransfer {
+            token_id: token_id.clone(),
+            recipient: String::from("random"),
+            reason: String::from("court order"),
+        },
+        Cw721ExecuteMsg::Permit {
+            payload: PermitPayload {
+                token_id: Some(token_id.clone()),
+                spender: String::from("spender"),
+                expires: None,
+                nonce: 0,
+                chain_id: env.block.chain_id.clone(),
+                contract_address: env.contract.address.to_string(),
+            },
+            pub_key: Binary::from(vec![2u8; 33]),
+            signature: Binary::from(vec![0u8; 64]),
+        },
+        Cw721ExecuteMsg::UpdateNftInfo {
+            token_id: token_id.clone(),
+            token_uri: None,
+            extension: None,
+        },
+    ];
+    for msg in msgs {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(CREATOR_ADDR, &[]),
+                msg,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NftExpired {
+                token_id: token_id.clone(),
+                mint_date,
+                expiration
+            }
+        );
+    }
+}
+
 #[test]
 fn test_approve_all_revoke_all() {
     let mut deps = mock_dependencies();
@@ -1543,3 +1623,84 @@ fn test_all_tokens() {
         }
     );
 }
+
+#[test]
+fn test_redeem_voucher() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut(), 1);
+    let env = mock_env();
+
+    let signing_key = SigningKey::from_bytes(&[2u8; 32].into()).unwrap();
+    let pub_key = Binary::from(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes(),
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MINTER_ADDR, &[]),
+            Cw721ExecuteMsg::SetVoucherSigner {
+                pub_key: Some(pub_key),
+            },
+        )
+        .unwrap();
+    let redeem_msg = |token_id: &str, image: &str, nonce: u64| {
+        let extension = Some(Metadata {
+            image: Some(image.to_string()),
+            ..Metadata::default()
+        });
+        let voucher = MintVoucher {
+            token_id: token_id.to_string(),
+            token_uri: None,
+            extension_hash: Binary::from(
+                Sha256::digest(to_json_vec(&extension).unwrap()).as_slice(),
+            ),
+            price: coin(0, "uatom"),
+            expires: Expiration::AtHeight(env.block.height + 10),
+            nonce,
+            chain_id: env.block.chain_id.clone(),
+            contract_address: env.contract.address.to_string(),
+        };
+        let hash = Sha256::digest(to_json_vec(&voucher).unwrap());
+        let signature: Signature = signing_key.sign_prehash(&hash).unwrap();
+        Cw721ExecuteMsg::RedeemVoucher {
+            voucher,
+            extension,
+            signature: Binary::from(signature.to_bytes().as_slice()),
+        }
+    };
+
+    // metadata of redeemed vouchers is validated
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &[]),
+            redeem_msg("grow", "http://example.com/grow.png", 1),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Cw721(Cw721ContractError::UrlSchemeNotAllowed {
+            field: "image".to_string(),
+            scheme: "http".to_string(),
+        })
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &[]),
+            redeem_msg("melt", "ipfs://melt.png", 2),
+        )
+        .unwrap();
+    let owner = contract
+        .query_owner_of_include_expired_nft(deps.as_ref(), env, "melt".to_string(), false, false)
+        .unwrap()
+        .owner;
+    assert_eq!(owner, "buyer");
+}
@@ -1,7 +1,7 @@
 use cosmwasm_std::{Binary, CustomMsg, DepsMut, Env, MessageInfo, Response};
 use cw721::{
     execute::Cw721Execute,
-    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg},
+    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, PermitPayload},
     Expiration,
 };
 use serde::de::DeserializeOwned;
@@ -12,8 +12,14 @@ use crate::{
     CONTRACT_VERSION,
 };
 
-impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
-    Cw721ExpirationContract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg>
+    Cw721ExpirationContract<
+        'a,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TCustomResponseMessage: CustomMsg,
@@ -34,6 +40,7 @@ where
             TMetadataExtension,
             TCustomResponseMessage,
             TMetadataExtensionMsg,
+            TQueryExtensionMsg,
         >::default();
         contract
             .expiration_days
@@ -47,6 +54,9 @@ where
                 symbol: msg.symbol,
                 minter: msg.minter,
                 withdraw_address: msg.withdraw_address,
+                snapshot_ownership: msg.snapshot_ownership,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
             },
             CONTRACT_NAME,
             CONTRACT_VERSION,
@@ -65,6 +75,7 @@ where
             TMetadataExtension,
             TCustomResponseMessage,
             TMetadataExtensionMsg,
+            TQueryExtensionMsg,
         >::default();
         match msg {
             Cw721ExecuteMsg::Mint {
@@ -95,6 +106,45 @@ where
             Cw721ExecuteMsg::Burn { token_id } => {
                 contract.burn_nft_include_nft_expired(deps, env, info, token_id)
             }
+            Cw721ExecuteMsg::RedeemVoucher {
+                voucher,
+                extension,
+                signature,
+            } => {
+                let mint_timstamp = env.block.time;
+                contract
+                    .mint_timestamps
+                    .save(deps.storage, &voucher.token_id, &mint_timstamp)?;
+                let res = contract
+                    .base_contract
+                    .redeem_voucher(deps, env, info, voucher, extension, signature)?
+                    .add_attribute("mint_timestamp", mint_timstamp.to_string());
+                Ok(res)
+            }
+            Cw721ExecuteMsg::UpdateNftInfo {
+                token_id,
+                token_uri,
+                extension,
+            } => contract.update_nft_info_include_nft_expired(
+                deps, env, info, token_id, token_uri, extension,
+            ),
+            Cw721ExecuteMsg::SetUser {
+                token_id,
+                user,
+                expires,
+            } => contract.set_user_include_nft_expired(deps, env, info, token_id, user, expires),
+            Cw721ExecuteMsg::ForceTransfer {
+                token_id,
+                recipient,
+                reason,
+            } => contract
+                .force_transfer_include_nft_expired(deps, env, info, token_id, recipient, reason),
+            Cw721ExecuteMsg::Permit {
+                payload,
+                pub_key,
+                signature,
+            } => contract.permit_include_nft_expired(deps, env, info, payload, pub_key, signature),
+            // not bound to a token, except `ForceBurn` which also cleans up expired tokens
             _ => {
                 let response = contract.base_contract.execute(deps, env, info, msg)?;
                 Ok(response)
@@ -118,7 +168,7 @@ where
             .save(deps.storage, &token_id, &mint_timstamp)?;
         let res = self
             .base_contract
-            .mint(deps, info, token_id, owner, token_uri, extension)?
+            .mint(deps, env, info, token_id, owner, token_uri, extension)?
             .add_attribute("mint_timestamp", mint_timstamp.to_string());
         Ok(res)
     }
@@ -191,4 +241,67 @@ where
         self.assert_nft_expired(deps.as_ref(), &env, token_id.as_str())?;
         Ok(self.base_contract.burn_nft(deps, env, info, token_id)?)
     }
+
+    pub fn update_nft_info_include_nft_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: Option<String>,
+        extension: TMetadataExtension,
+    ) -> Result<Response<TCustomResponseMessage>, ContractError> {
+        self.assert_nft_expired(deps.as_ref(), &env, token_id.as_str())?;
+        Ok(self
+            .base_contract
+            .update_nft_info(deps, env, info, token_id, token_uri, extension)?)
+    }
+
+    pub fn set_user_include_nft_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        user: Option<String>,
+        expires: Option<Expiration>,
+    ) -> Result<Response<TCustomResponseMessage>, ContractError> {
+        self.assert_nft_expired(deps.as_ref(), &env, token_id.as_str())?;
+        Ok(self
+            .base_contract
+            .set_user(deps, env, info, token_id, user, expires)?)
+    }
+
+    pub fn force_transfer_include_nft_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        recipient: String,
+        reason: String,
+    ) -> Result<Response<TCustomResponseMessage>, ContractError> {
+        self.assert_nft_expired(deps.as_ref(), &env, token_id.as_str())?;
+        Ok(self
+            .base_contract
+            .force_transfer(deps, env, info, token_id, recipient, reason)?)
+    }
+
+    /// Permits for all tokens of the owner are not checked, same as `ApproveAll`
+    pub fn permit_include_nft_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        payload: PermitPayload,
+        pub_key: Binary,
+        signature: Binary,
+    ) -> Result<Response<TCustomResponseMessage>, ContractError> {
+        if let Some(token_id) = &payload.token_id {
+            self.assert_nft_expired(deps.as_ref(), &env, token_id)?;
+        }
+        Ok(self
+            .base_contract
+            .permit(deps, env, info, payload, pub_key, signature)?)
+    }
 }
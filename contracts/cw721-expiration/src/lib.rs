@@ -29,8 +29,10 @@ pub mod entry {
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response};
-    use cw721::{msg::Cw721ExecuteMsg, state::DefaultOptionMetadataExtension};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response};
+    use cw721::{
+        execute::Cw721Execute, msg::Cw721ExecuteMsg, state::DefaultOptionMetadataExtension,
+    };
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -41,7 +43,8 @@ pub mod entry {
         msg: InstantiateMsg,
     ) -> Result<Response, ContractError> {
         let contract =
-            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default(
+            );
         contract.instantiate(deps, env, info, msg)
     }
 
@@ -53,18 +56,24 @@ pub mod entry {
         msg: Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>,
     ) -> Result<Response, ContractError> {
         let contract =
-            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default(
+            );
         contract.execute(deps, env, info, msg)
     }
 
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        let contract =
+            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default(
+            );
+        Ok(contract.base_contract.reply(deps, env, msg)?)
+    }
+
     #[entry_point]
-    pub fn query(
-        deps: Deps,
-        env: Env,
-        msg: QueryMsg<DefaultOptionMetadataExtension>,
-    ) -> Result<Binary, ContractError> {
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg<Empty>) -> Result<Binary, ContractError> {
         let contract =
-            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default(
+            );
         contract.query(deps, env, msg)
     }
 
@@ -99,6 +108,7 @@ mod tests {
                 symbol: "collection_symbol".into(),
                 minter: Some("minter".into()),
                 withdraw_address: None,
+                snapshot_ownership: None,
             },
         )
         .unwrap_err();
@@ -115,6 +125,7 @@ mod tests {
                 symbol: "".into(),
                 minter: Some("minter".into()),
                 withdraw_address: None,
+                snapshot_ownership: None,
             },
         )
         .unwrap();
@@ -130,7 +141,7 @@ mod tests {
 
         assert_eq!(
             1,
-            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty>::default()
+            Cw721ExpirationContract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default()
                 .expiration_days
                 .load(deps.as_ref().storage)
                 .unwrap()
@@ -24,11 +24,15 @@ pub struct InstantiateMsg {
     pub minter: Option<String>,
 
     pub withdraw_address: Option<String>,
+
+    /// Records owner changes per height, enabling `OwnerOfAtHeight` and `TokensAtHeight` queries.
+    /// Can only be set on instantiate, defaults to false.
+    pub snapshot_ownership: Option<bool>,
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg<TMetadataExtension> {
+pub enum QueryMsg<TQueryExtensionMsg> {
     // -------- below adds `include_expired_nft` prop to cw721/src/msg.rs --------
     /// Return the owner of the given token, error if token does not exist
     #[returns(cw721::msg::OwnerOfResponse)]
@@ -139,9 +143,9 @@ pub enum QueryMsg<TMetadataExtension> {
     #[returns(MinterResponse)]
     Minter {},
 
-    /// Extension query
-    #[returns(())]
-    Extension { msg: TMetadataExtension },
+    /// Custom query of a contract extending cw721-expiration
+    #[returns(cw721::msg::ExtensionQueryResponse)]
+    Extension { msg: TQueryExtensionMsg },
 
     #[returns(Option<String>)]
     GetWithdrawAddress {},
@@ -9,8 +9,14 @@ use serde::Serialize;
 
 use crate::{error::ContractError, msg::QueryMsg, state::Cw721ExpirationContract};
 
-impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
-    Cw721ExpirationContract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg>
+    Cw721ExpirationContract<
+        'a,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TCustomResponseMessage: CustomMsg,
@@ -20,12 +26,13 @@ where
         &self,
         deps: Deps,
         env: Env,
-        msg: QueryMsg<TMetadataExtension>,
+        msg: QueryMsg<TQueryExtensionMsg>,
     ) -> Result<Binary, ContractError> {
         let contract = Cw721ExpirationContract::<
             TMetadataExtension,
             TCustomResponseMessage,
             TMetadataExtensionMsg,
+            TQueryExtensionMsg,
         >::default();
         match msg {
             // -------- msgs with `include_expired_nft` prop --------
@@ -169,9 +176,9 @@ where
             QueryMsg::Minter {} => Ok(to_json_binary(
                 &contract.base_contract.query_minter(deps.storage)?,
             )?),
-            QueryMsg::Extension { msg } => Ok(to_json_binary(
-                &contract.base_contract.query_extension(deps, env, msg)?,
-            )?),
+            QueryMsg::Extension { msg } => {
+                Ok(contract.base_contract.query_extension(deps, env, msg)?)
+            }
             QueryMsg::GetWithdrawAddress {} => Ok(to_json_binary(
                 &contract.base_contract.query_withdraw_address(deps)?,
             )?),
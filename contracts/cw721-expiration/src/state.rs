@@ -16,22 +16,30 @@ pub struct Cw721ExpirationContract<
     TCustomResponseMessage,
     // Message passed for updating metadata.
     TMetadataExtensionMsg,
+    // Message passed for custom queries, handled by `Cw721Query::query_extension`.
+    TQueryExtensionMsg,
 > where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
 {
     pub expiration_days: Item<'a, u16>, // max 65535 days
     pub mint_timestamps: Map<'a, &'a str, Timestamp>,
-    pub base_contract:
-        Cw721Contract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>,
+    pub base_contract: Cw721Contract<
+        'a,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >,
 }
 
-impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg> Default
+impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg> Default
     for Cw721ExpirationContract<
         'static,
         TMetadataExtension,
         TCustomResponseMessage,
         TMetadataExtensionMsg,
+        TQueryExtensionMsg,
     >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
@@ -1,20 +1,36 @@
 use std::marker::PhantomData;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, CONFIG};
+use crate::msg::{
+    ConfigResponse, CurrentPhaseResponse, CurrentPriceResponse, DistributionResponse, ExecuteMsg,
+    InstantiateMsg, MerkleRootResponse, MigrateMsg, MintedByResponse, OwnershipResponse,
+    PayeesResponse, ProceedsResponse, QueryMsg, RebateResponse, ReceiveMsg, SalePhasesResponse,
+    SaleStatusResponse, TokenMetadata, TokenMetadataResponse,
+};
+use crate::state::{
+    Config, Payee, RebateDeposit, Reservation, SalePhase, TokenAssignment, ALLOWLIST_MINTS, CONFIG,
+    DISTRIBUTED, LAST_AUCTION_PRICE, MERKLE_ROOT, MINTED_BY, PAYEES, PENDING_OWNER, PHASE_MINTS,
+    PROCEEDS, RANDOM_SEED, REBATE_DEPOSITS, REBATE_TOTAL, REFUNDABLE, REMAINING_IDS, RESERVATIONS,
+    SALE_PHASES, TOKEN_METADATA,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Reply, ReplyOn, Response,
-    StdResult, SubMsg, Uint128, WasmMsg,
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw20::Cw20ReceiveMsg;
+use cw2::{ensure_from_older_version, set_contract_version};
+use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw721::helpers::Cw721Contract;
-use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg};
-use cw721::state::DefaultOptionMetadataExtension;
-use cw_utils::parse_reply_instantiate_data;
+use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg};
+use cw721::query::{DEFAULT_LIMIT, MAX_LIMIT};
+use cw721::state::{CollectionInfo, DefaultOptionMetadataExtension, MetadataValidation};
+use cw_ownable::{Action, Ownership};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, parse_reply_instantiate_data, Expiration};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-fixed-price";
@@ -22,38 +38,130 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 
+const MAX_BPS: u64 = 10_000;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    if msg.unit_price == Uint128::new(0) {
+    // unused in a dutch auction
+    if msg.unit_price == Uint128::new(0) && msg.dutch_auction.is_none() {
         return Err(ContractError::InvalidUnitPrice {});
     }
 
-    if msg.max_tokens == 0 {
+    let start_token_id = msg.start_token_id.unwrap_or_default();
+    if msg.max_tokens == 0 || start_token_id.checked_add(msg.max_tokens).is_none() {
         return Err(ContractError::InvalidMaxTokens {});
     }
 
+    if msg.max_per_wallet == Some(0) {
+        return Err(ContractError::InvalidMaxPerWallet {});
+    }
+
+    // exactly one payment route must be configured
+    if msg.cw20_address.is_some() == msg.native_denom.is_some() {
+        return Err(ContractError::InvalidPaymentConfig {});
+    }
+
+    // the wasm admin of an existing collection can't be changed by this contract
+    if msg.cw721_address.is_some() && msg.cw721_admin.is_some() {
+        return Err(ContractError::InvalidCw721Admin {});
+    }
+
+    if let Some(auction) = &msg.dutch_auction {
+        if auction.interval == 0
+            || auction.price_step.is_zero()
+            || auction.floor_price.is_zero()
+            || auction.floor_price > auction.start_price
+        {
+            return Err(ContractError::InvalidDutchAuction {});
+        }
+    }
+
+    let assignment = msg.assignment.unwrap_or_default();
+    if let TokenAssignment::Random {
+        commitment,
+        reveal_deadline,
+    } = &assignment
+    {
+        let mut commitment_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(commitment, &mut commitment_buf)
+            .map_err(|_| ContractError::InvalidCommitment {})?;
+        if matches!(reveal_deadline, Expiration::Never {}) || reveal_deadline.is_expired(&env.block)
+        {
+            return Err(ContractError::InvalidRevealDeadline {});
+        }
+    }
+
+    let cw721_address = msg
+        .cw721_address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let cw721 = cw721_address.clone().map(|cw721_address| {
+        Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
+            cw721_address,
+            PhantomData,
+            PhantomData,
+        )
+    });
+
+    // tokens of an existing collection are sold under its name and symbol
+    let (name, symbol) = match &cw721 {
+        Some(cw721) => {
+            let collection_info: CollectionInfo =
+                cw721.query(&deps.querier, Cw721QueryMsg::ContractInfo {})?;
+            (collection_info.name, collection_info.symbol)
+        }
+        None => (msg.name.clone(), msg.symbol.clone()),
+    };
+
     let config = Config {
-        cw721_address: None,
+        cw721_address,
         cw20_address: msg.cw20_address,
+        native_denom: msg.native_denom,
         unit_price: msg.unit_price,
         max_tokens: msg.max_tokens,
+        max_per_wallet: msg.max_per_wallet,
         owner: info.sender,
-        name: msg.name.clone(),
-        symbol: msg.symbol.clone(),
+        name,
+        symbol,
         token_uri: msg.token_uri.clone(),
+        base_uri: msg.base_uri,
         extension: msg.extension.clone(),
         unused_token_id: 0,
+        start_token_id,
+        assignment,
+        dutch_auction: msg.dutch_auction,
+        paused: false,
     };
 
     CONFIG.save(deps.storage, &config)?;
 
+    if let Some(cw721) = cw721 {
+        // sell tokens of an existing collection, this contract must be or become its minter
+        let ownership: Ownership<Addr> = cw721.query(&deps.querier, Cw721QueryMsg::Ownership {})?;
+        if ownership.owner.as_ref() == Some(&env.contract.address) {
+            return Ok(Response::new());
+        }
+        let pending_expired = ownership
+            .pending_expiry
+            .is_some_and(|expiry| expiry.is_expired(&env.block));
+        if ownership.pending_owner.as_ref() != Some(&env.contract.address) || pending_expired {
+            return Err(ContractError::NotMinter {});
+        }
+        let accept_msg = cw721.call(Cw721ExecuteMsg::UpdateOwnership(Action::AcceptOwnership))?;
+        return Ok(Response::new().add_message(accept_msg));
+    }
+
+    let admin = msg
+        .cw721_admin
+        .map(|admin| deps.api.addr_validate(&admin))
+        .transpose()?;
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         msg: WasmMsg::Instantiate {
             code_id: msg.token_code_id,
@@ -62,9 +170,12 @@ pub fn instantiate(
                 symbol: msg.symbol,
                 minter: None,
                 withdraw_address: msg.withdraw_address,
+                snapshot_ownership: msg.snapshot_ownership,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
             })?,
             funds: vec![],
-            admin: None,
+            admin: admin.map(String::from),
             label: String::from("Instantiate fixed price NFT contract"),
         }
         .into(),
@@ -76,6 +187,16 @@ pub fn instantiate(
     Ok(Response::new().add_submessages(sub_msg))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // only migrate from an older version of this contract
+    let from_version = ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from_version.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
 // Reply callback triggered from cw721 contract instantiation
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -97,32 +218,184 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::MerkleRoot {} => to_json_binary(&query_merkle_root(deps)?),
+        QueryMsg::MintedBy { address } => to_json_binary(&query_minted_by(deps, address)?),
+        QueryMsg::SalePhases {} => to_json_binary(&query_sale_phases(deps)?),
+        QueryMsg::CurrentPhase {} => to_json_binary(&query_current_phase(deps, env)?),
+        QueryMsg::Proceeds {} => to_json_binary(&query_proceeds(deps)?),
+        QueryMsg::Payees {} => to_json_binary(&query_payees(deps)?),
+        QueryMsg::TokenMetadata { start_after, limit } => {
+            to_json_binary(&query_token_metadata(deps, start_after, limit)?)
+        }
+        QueryMsg::CurrentPrice {} => to_json_binary(&query_current_price(deps, env)?),
+        QueryMsg::Rebate { address } => to_json_binary(&query_rebate(deps, env, address)?),
+        QueryMsg::SaleStatus {} => to_json_binary(&query_sale_status(deps, env)?),
+        QueryMsg::Distribution {} => to_json_binary(&query_distribution(deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&query_ownership(deps)?),
     }
 }
 
+fn query_distribution(deps: Deps) -> StdResult<DistributionResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let reserved = match config.assignment {
+        TokenAssignment::Sequential => 0,
+        TokenAssignment::Random { .. } => config.unused_token_id,
+    };
+    Ok(DistributionResponse {
+        revealed: RANDOM_SEED.may_load(deps.storage)?.is_some(),
+        reserved,
+        distributed: DISTRIBUTED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    Ok(OwnershipResponse {
+        owner: CONFIG.load(deps.storage)?.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+fn query_sale_status(deps: Deps, env: Env) -> StdResult<SaleStatusResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let phases_set = SALE_PHASES.may_load(deps.storage)?.is_some();
+    let phase = query_current_phase(deps, env.clone())?.phase;
+    let auction_started = config
+        .dutch_auction
+        .as_ref()
+        .map_or(true, |auction| env.block.time >= auction.start_time);
+    let open = config.cw721_address.is_some()
+        && !config.paused
+        && auction_started
+        && !RANDOM_SEED.exists(deps.storage)
+        && !reveal_expired(deps.storage, &env.block, &config)?
+        && config.unused_token_id < config.max_tokens
+        && (!phases_set || phase.is_some());
+    Ok(SaleStatusResponse {
+        open,
+        paused: config.paused,
+        minted: config.unused_token_id,
+        max_tokens: config.max_tokens,
+        price: current_price(&config, phase.as_ref(), &env.block),
+        phase: phase.map(|phase| phase.name),
+    })
+}
+
+fn query_current_price(deps: Deps, env: Env) -> StdResult<CurrentPriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let phase = query_current_phase(deps, env.clone())?.phase;
+    Ok(CurrentPriceResponse {
+        price: current_price(&config, phase.as_ref(), &env.block),
+    })
+}
+
+fn query_rebate(deps: Deps, env: Env, address: String) -> StdResult<RebateResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    let deposit = REBATE_DEPOSITS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let claimable = match clearing_price(deps.storage, &env.block, &config)? {
+        Some(price) => deposit
+            .paid
+            .saturating_sub(price.saturating_mul(deposit.quantity.into())),
+        None => Uint128::zero(),
+    };
+    Ok(RebateResponse {
+        paid: deposit.paid,
+        quantity: deposit.quantity,
+        claimable,
+    })
+}
+
+fn query_token_metadata(
+    deps: Deps,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<TokenMetadataResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let entries = TOKEN_METADATA
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token_id, metadata)| TokenMetadata { token_id, metadata }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TokenMetadataResponse { entries })
+}
+
+fn query_proceeds(deps: Deps) -> StdResult<ProceedsResponse> {
+    let proceeds = PROCEEDS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ProceedsResponse {
+        collected: proceeds.collected,
+        withdrawn: proceeds.withdrawn,
+    })
+}
+
+fn query_payees(deps: Deps) -> StdResult<PayeesResponse> {
+    Ok(PayeesResponse {
+        payees: PAYEES.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_sale_phases(deps: Deps) -> StdResult<SalePhasesResponse> {
+    Ok(SalePhasesResponse {
+        phases: SALE_PHASES.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_current_phase(deps: Deps, env: Env) -> StdResult<CurrentPhaseResponse> {
+    let phase = SALE_PHASES
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|phase| is_active(phase, &env.block));
+    Ok(CurrentPhaseResponse { phase })
+}
+
+fn query_minted_by(deps: Deps, address: String) -> StdResult<MintedByResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(MintedByResponse {
+        count: MINTED_BY
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default(),
+    })
+}
+
+fn query_merkle_root(deps: Deps) -> StdResult<MerkleRootResponse> {
+    Ok(MerkleRootResponse {
+        merkle_root: MERKLE_ROOT.may_load(deps.storage)?,
+    })
+}
+
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
         owner: config.owner,
         cw20_address: config.cw20_address,
+        native_denom: config.native_denom,
         cw721_address: config.cw721_address,
         max_tokens: config.max_tokens,
+        max_per_wallet: config.max_per_wallet,
         unit_price: config.unit_price,
         name: config.name,
         symbol: config.symbol,
         token_uri: config.token_uri,
+        base_uri: config.base_uri,
         extension: config.extension,
         unused_token_id: config.unused_token_id,
+        start_token_id: config.start_token_id,
+        assignment: config.assignment,
+        dutch_auction: config.dutch_auction,
+        paused: config.paused,
     })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
@@ -131,174 +404,1048 @@ pub fn execute(
             sender,
             amount,
             msg,
-        }) => execute_receive(deps, info, sender, amount, msg),
+        }) => execute_receive(deps, env, info, sender, amount, msg),
+        ExecuteMsg::Mint {
+            quantity,
+            allowance,
+            proof,
+        } => execute_mint(
+            deps,
+            env,
+            info,
+            ReceiveMsg {
+                quantity,
+                allowance,
+                proof: proof.unwrap_or_default(),
+            },
+        ),
+        ExecuteMsg::SetMerkleRoot { merkle_root } => {
+            execute_set_merkle_root(deps, info, merkle_root)
+        }
+        ExecuteMsg::SetSalePhases { phases } => execute_set_sale_phases(deps, info, phases),
+        ExecuteMsg::WithdrawProceeds { amount, recipient } => {
+            execute_withdraw_proceeds(deps, env, info, amount, recipient)
+        }
+        ExecuteMsg::SetPayees { payees } => execute_set_payees(deps, info, payees),
+        ExecuteMsg::UploadMetadata { entries } => execute_upload_metadata(deps, info, entries),
+        ExecuteMsg::RevealSecret { secret } => execute_reveal_secret(deps, env, info, secret),
+        ExecuteMsg::DistributeTokens { limit } => execute_distribute_tokens(deps, limit),
+        ExecuteMsg::ClaimRefund {} => execute_claim_refund(deps, env, info),
+        ExecuteMsg::ClaimRebate {} => execute_claim_rebate(deps, env, info),
+        ExecuteMsg::UpdateConfig {
+            unit_price,
+            max_tokens,
+            token_uri,
+        } => execute_update_config(deps, info, unit_price, max_tokens, token_uri),
+        ExecuteMsg::Pause {} => execute_set_paused(deps, info, true),
+        ExecuteMsg::Resume {} => execute_set_paused(deps, info, false),
+        ExecuteMsg::TransferOwnership { new_owner } => {
+            execute_transfer_ownership(deps, info, new_owner)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::UpdateCollectionOwnership { action } => {
+            execute_update_collection_ownership(deps, info, action)
+        }
+    }
+}
+
+pub fn execute_set_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    match &merkle_root {
+        Some(merkle_root) => {
+            // check merkle root length
+            let mut root_buf: [u8; 32] = [0; 32];
+            hex::decode_to_slice(merkle_root, &mut root_buf)
+                .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+            MERKLE_ROOT.save(deps.storage, merkle_root)?;
+        }
+        None => MERKLE_ROOT.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_merkle_root")
+        .add_attribute("merkle_root", merkle_root.unwrap_or_default()))
+}
+
+pub fn execute_set_sale_phases(
+    deps: DepsMut,
+    info: MessageInfo,
+    phases: Vec<SalePhase>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
     }
+    for (i, phase) in phases.iter().enumerate() {
+        let invalid = || ContractError::InvalidSalePhase {
+            name: phase.name.clone(),
+        };
+        if phases[..i].iter().any(|p| p.name == phase.name) {
+            return Err(invalid());
+        }
+        // start must be before end, both of the same kind unless the phase never ends
+        if phase.start.partial_cmp(&phase.end) != Some(Ordering::Less) {
+            return Err(invalid());
+        }
+        if (phase.unit_price.is_zero() && config.dutch_auction.is_none())
+            || phase.max_per_wallet == Some(0)
+        {
+            return Err(invalid());
+        }
+        if let Some(merkle_root) = &phase.merkle_root {
+            let mut root_buf: [u8; 32] = [0; 32];
+            hex::decode_to_slice(merkle_root, &mut root_buf)
+                .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+        }
+    }
+    if phases.is_empty() {
+        SALE_PHASES.remove(deps.storage);
+    } else {
+        SALE_PHASES.save(deps.storage, &phases)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_sale_phases")
+        .add_attribute("phases", phases.len().to_string()))
 }
 
 pub fn execute_receive(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     sender: String,
     amount: Uint128,
-    _msg: Binary,
+    msg: Binary,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
-    if config.cw20_address != info.sender {
+    if config.cw20_address.as_ref() != Some(&info.sender) {
         return Err(ContractError::UnauthorizedTokenContract {});
     }
 
-    if config.cw721_address.is_none() {
-        return Err(ContractError::Uninitialized {});
+    let receive_msg: ReceiveMsg = if msg.is_empty() {
+        ReceiveMsg::default()
+    } else {
+        from_json(&msg)?
+    };
+    let (res, excess) = mint_tokens(
+        deps,
+        &env,
+        &mut config,
+        sender.clone(),
+        amount,
+        &receive_msg,
+    )?;
+
+    if excess.is_zero() {
+        return Ok(res);
     }
+    Ok(res.add_message(payment_msg(&config, sender, excess)?))
+}
 
-    if config.unused_token_id >= config.max_tokens {
-        return Err(ContractError::SoldOut {});
+pub fn execute_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive_msg: ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let denom = config
+        .native_denom
+        .clone()
+        .ok_or(ContractError::NativePaymentDisabled {})?;
+
+    let amount = must_pay(&info, &denom)?;
+    let (res, excess) = mint_tokens(
+        deps,
+        &env,
+        &mut config,
+        info.sender.to_string(),
+        amount,
+        &receive_msg,
+    )?;
+
+    if excess.is_zero() {
+        return Ok(res);
     }
+    Ok(res.add_message(payment_msg(&config, info.sender.to_string(), excess)?))
+}
 
-    if amount != config.unit_price {
-        return Err(ContractError::WrongPaymentAmount {});
+pub fn execute_withdraw_proceeds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
     }
 
-    let mint_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::Mint {
-        token_id: config.unused_token_id.to_string(),
-        owner: sender,
-        token_uri: config.token_uri.clone().into(),
-        extension: config.extension.clone(),
+    let mut proceeds = PROCEEDS.may_load(deps.storage)?.unwrap_or_default();
+    let reserved = reserved_rebates(deps.storage, &env.block, &config)?;
+    let available = match config.assignment {
+        // refundable until the secret is revealed
+        TokenAssignment::Random { .. } if !RANDOM_SEED.exists(deps.storage) => Uint128::zero(),
+        _ => (proceeds.collected - proceeds.withdrawn).saturating_sub(reserved),
+    };
+    let amount = amount.unwrap_or(available);
+    if amount.is_zero() || amount > available {
+        return Err(ContractError::InsufficientProceeds {});
+    }
+    proceeds.withdrawn += amount;
+    PROCEEDS.save(deps.storage, &proceeds)?;
+
+    let shares = match recipient {
+        Some(recipient) => vec![(deps.api.addr_validate(&recipient)?, amount)],
+        None => match PAYEES.may_load(deps.storage)? {
+            Some(payees) => {
+                // last payee receives the rounding remainder
+                let mut remaining = amount;
+                let last = payees.len() - 1;
+                payees
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, payee)| {
+                        let share = if i == last {
+                            remaining
+                        } else {
+                            amount.multiply_ratio(payee.bps, MAX_BPS)
+                        };
+                        remaining -= share;
+                        (payee.address, share)
+                    })
+                    .collect()
+            }
+            None => vec![(config.owner.clone(), amount)],
+        },
     };
 
-    match config.cw721_address.clone() {
-        Some(cw721) => {
-            let callback = Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
-                cw721,
-                PhantomData,
-                PhantomData,
-            )
-            .call(mint_msg)?;
-            config.unused_token_id += 1;
-            CONFIG.save(deps.storage, &config)?;
+    let mut res = Response::new()
+        .add_attribute("action", "withdraw_proceeds")
+        .add_attribute("amount", amount);
+    for (address, share) in shares {
+        if !share.is_zero() {
+            res = res.add_message(payment_msg(&config, address.to_string(), share)?);
+        }
+    }
+    Ok(res)
+}
+
+pub fn execute_set_payees(
+    deps: DepsMut,
+    info: MessageInfo,
+    payees: Vec<Payee>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
 
-            Ok(Response::new().add_message(callback))
+    if payees.is_empty() {
+        PAYEES.remove(deps.storage);
+    } else {
+        for payee in &payees {
+            deps.api.addr_validate(payee.address.as_str())?;
+            if payee.bps == 0 {
+                return Err(ContractError::InvalidPayees {});
+            }
+        }
+        let total_bps: u64 = payees.iter().map(|payee| u64::from(payee.bps)).sum();
+        if total_bps != MAX_BPS {
+            return Err(ContractError::InvalidPayees {});
         }
-        None => Err(ContractError::Cw721NotLinked {}),
+        PAYEES.save(deps.storage, &payees)?;
     }
+    Ok(Response::new()
+        .add_attribute("action", "set_payees")
+        .add_attribute("payees", payees.len().to_string()))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{from_json, to_json_binary, CosmosMsg, SubMsgResponse, SubMsgResult};
-    use cw721::state::DefaultOptionMetadataExtension;
-    use prost::Message;
+pub fn execute_upload_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    entries: Vec<TokenMetadata>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if config.unused_token_id > 0 {
+        return Err(ContractError::MetadataLocked {});
+    }
 
-    const NFT_CONTRACT_ADDR: &str = "nftcontract";
+    // rejected now rather than failing the mint of the token
+    let cw721_address = config
+        .cw721_address
+        .ok_or(ContractError::Uninitialized {})?;
+    let cw721 = Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
+        cw721_address,
+        PhantomData,
+        PhantomData,
+    );
+    // collections of older cw721 versions don't support the query, default rules apply
+    let rules: MetadataValidation = cw721
+        .query(&deps.querier, Cw721QueryMsg::GetMetadataValidation {})
+        .unwrap_or_default();
+
+    for entry in &entries {
+        let index = entry.token_id.checked_sub(config.start_token_id);
+        if index.map_or(true, |index| index >= config.max_tokens) {
+            return Err(ContractError::InvalidTokenId {
+                token_id: entry.token_id,
+            });
+        }
+        entry.metadata.validate(&rules)?;
+        TOKEN_METADATA.save(deps.storage, entry.token_id, &entry.metadata)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "upload_metadata")
+        .add_attribute("entries", entries.len().to_string()))
+}
 
-    // Type for replies to contract instantiate messes
-    #[derive(Clone, PartialEq, Message)]
-    struct MsgInstantiateContractResponse {
-        #[prost(string, tag = "1")]
-        pub contract_address: ::prost::alloc::string::String,
-        #[prost(bytes, tag = "2")]
-        pub data: ::prost::alloc::vec::Vec<u8>,
+pub fn execute_reveal_secret(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let (commitment, reveal_deadline) = match &config.assignment {
+        TokenAssignment::Random {
+            commitment,
+            reveal_deadline,
+        } => (commitment, reveal_deadline),
+        TokenAssignment::Sequential => return Err(ContractError::InvalidSecret {}),
+    };
+    if RANDOM_SEED.exists(deps.storage) {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    if reveal_deadline.is_expired(&env.block) {
+        return Err(ContractError::RevealDeadlinePassed {});
     }
 
-    #[test]
-    fn initialization() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
-        };
+    let mut commitment_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(commitment, &mut commitment_buf)
+        .map_err(|_| ContractError::InvalidCommitment {})?;
+    if Sha256::digest(secret.as_bytes())[..] != commitment_buf {
+        return Err(ContractError::InvalidSecret {});
+    }
 
-        let info = mock_info("owner", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    // the reveal block keeps the owner from knowing the assignment while the sale is open
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    hasher.update(env.block.chain_id.as_bytes());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    RANDOM_SEED.save(deps.storage, &hasher.finalize().to_vec())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal_secret")
+        .add_attribute("reserved", config.unused_token_id.to_string()))
+}
 
-        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+pub fn execute_distribute_tokens(
+    deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let seed = RANDOM_SEED
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotRevealed {})?;
+    let cw721_address = config
+        .cw721_address
+        .clone()
+        .ok_or(ContractError::Uninitialized {})?;
+    let cw721 = Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
+        cw721_address,
+        PhantomData,
+        PhantomData,
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let distributed = DISTRIBUTED.may_load(deps.storage)?.unwrap_or_default();
+    let end = config
+        .unused_token_id
+        .min(distributed.saturating_add(limit));
+    let mut res = Response::new();
+    for slot in distributed..end {
+        let reservation = RESERVATIONS.load(deps.storage, slot)?;
+        RESERVATIONS.remove(deps.storage, slot);
+        let token_id = config.start_token_id
+            + draw_token_id(deps.storage, &seed, &config, slot, &reservation)?;
+        let mint_msg = mint_msg(
+            deps.storage,
+            &config,
+            token_id,
+            reservation.buyer.to_string(),
+        )?;
+        res = res.add_message(cw721.call(mint_msg)?);
+    }
+    DISTRIBUTED.save(deps.storage, &end)?;
 
-        assert_eq!(
-            res.messages,
-            vec![SubMsg {
-                msg: WasmMsg::Instantiate {
-                    code_id: msg.token_code_id,
-                    msg: to_json_binary(&Cw721InstantiateMsg {
-                        name: msg.name.clone(),
-                        symbol: msg.symbol.clone(),
-                        minter: None,
-                        withdraw_address: None,
-                    })
-                    .unwrap(),
-                    funds: vec![],
-                    admin: None,
-                    label: String::from("Instantiate fixed price NFT contract"),
-                }
-                .into(),
-                id: INSTANTIATE_TOKEN_REPLY_ID,
-                gas_limit: None,
-                reply_on: ReplyOn::Success,
-            }]
-        );
+    Ok(res
+        .add_attribute("action", "distribute_tokens")
+        .add_attribute("distributed", (end - distributed).to_string()))
+}
 
-        let instantiate_reply = MsgInstantiateContractResponse {
-            contract_address: "nftcontract".to_string(),
-            data: vec![2u8; 32769],
-        };
-        let mut encoded_instantiate_reply =
-            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
-        instantiate_reply
-            .encode(&mut encoded_instantiate_reply)
-            .unwrap();
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !reveal_expired(deps.storage, &env.block, &config)? {
+        return Err(ContractError::RefundUnavailable {});
+    }
+    let refund = REFUNDABLE
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoRefund {})?;
+    REFUNDABLE.remove(deps.storage, &info.sender);
+
+    // the refund covers the rebate deposit
+    if let Some(deposit) = REBATE_DEPOSITS.may_load(deps.storage, &info.sender)? {
+        REBATE_DEPOSITS.remove(deps.storage, &info.sender);
+        let mut total = REBATE_TOTAL.load(deps.storage)?;
+        total.paid -= deposit.paid;
+        total.quantity -= deposit.quantity;
+        REBATE_TOTAL.save(deps.storage, &total)?;
+    }
+    let mut proceeds = PROCEEDS.load(deps.storage)?;
+    proceeds.collected -= refund;
+    PROCEEDS.save(deps.storage, &proceeds)?;
+
+    let res = Response::new()
+        .add_attribute("action", "claim_refund")
+        .add_attribute("refund", refund);
+    if refund.is_zero() {
+        return Ok(res);
+    }
+    Ok(res.add_message(payment_msg(&config, info.sender.to_string(), refund)?))
+}
 
-        let reply_msg = Reply {
-            id: INSTANTIATE_TOKEN_REPLY_ID,
-            result: SubMsgResult::Ok(SubMsgResponse {
-                events: vec![],
-                data: Some(encoded_instantiate_reply.into()),
-            }),
-        };
-        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+pub fn execute_claim_rebate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let deposit = REBATE_DEPOSITS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoRebate {})?;
+    let price = clearing_price(deps.storage, &env.block, &config)?
+        .ok_or(ContractError::AuctionNotSettled {})?;
+
+    let rebate = deposit
+        .paid
+        .saturating_sub(price.saturating_mul(deposit.quantity.into()));
+    REBATE_DEPOSITS.remove(deps.storage, &info.sender);
+    let mut total = REBATE_TOTAL.load(deps.storage)?;
+    total.paid -= deposit.paid;
+    total.quantity -= deposit.quantity;
+    REBATE_TOTAL.save(deps.storage, &total)?;
+    let mut proceeds = PROCEEDS.load(deps.storage)?;
+    proceeds.collected -= rebate;
+    PROCEEDS.save(deps.storage, &proceeds)?;
+    if let Some(refundable) = REFUNDABLE.may_load(deps.storage, &info.sender)? {
+        REFUNDABLE.save(deps.storage, &info.sender, &(refundable - rebate))?;
+    }
 
-        let query_msg = QueryMsg::GetConfig {};
-        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let config: Config = from_json(res).unwrap();
-        assert_eq!(
-            config,
-            Config {
-                owner: Addr::unchecked("owner"),
-                cw20_address: msg.cw20_address,
-                cw721_address: Some(Addr::unchecked(NFT_CONTRACT_ADDR)),
-                max_tokens: msg.max_tokens,
-                unit_price: msg.unit_price,
-                name: msg.name,
-                symbol: msg.symbol,
-                token_uri: msg.token_uri,
-                extension: None,
-                unused_token_id: 0
-            }
-        );
+    let res = Response::new()
+        .add_attribute("action", "claim_rebate")
+        .add_attribute("rebate", rebate);
+    if rebate.is_zero() {
+        return Ok(res);
     }
+    Ok(res.add_message(payment_msg(&config, info.sender.to_string(), rebate)?))
+}
 
-    #[test]
-    fn invalid_unit_price() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(0),
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    unit_price: Option<Uint128>,
+    max_tokens: Option<u32>,
+    token_uri: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(unit_price) = unit_price {
+        if unit_price.is_zero() && config.dutch_auction.is_none() {
+            return Err(ContractError::InvalidUnitPrice {});
+        }
+        config.unit_price = unit_price;
+    }
+    if let Some(max_tokens) = max_tokens {
+        // pool of remaining ids for random assignment is sized by max tokens
+        if max_tokens > config.max_tokens
+            || max_tokens < config.unused_token_id
+            || max_tokens == 0
+            || matches!(config.assignment, TokenAssignment::Random { .. })
+        {
+            return Err(ContractError::InvalidMaxTokens {});
+        }
+        config.max_tokens = max_tokens;
+    }
+    if let Some(token_uri) = token_uri {
+        config.token_uri = token_uri;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    config.paused = paused;
+    CONFIG.save(deps.storage, &config)?;
+
+    let action = if paused { "pause" } else { "resume" };
+    Ok(Response::new().add_attribute("action", action))
+}
+
+pub fn execute_transfer_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    PENDING_OWNER.save(deps.storage, &new_owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_ownership")
+        .add_attribute("pending_owner", new_owner))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if PENDING_OWNER.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+    let mut config = CONFIG.load(deps.storage)?;
+    config.owner = info.sender.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", info.sender))
+}
+
+pub fn execute_update_collection_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    action: Action,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let cw721_address = config
+        .cw721_address
+        .ok_or(ContractError::Uninitialized {})?;
+    let cw721 = Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
+        cw721_address,
+        PhantomData,
+        PhantomData,
+    );
+
+    Ok(Response::new()
+        .add_message(cw721.call(Cw721ExecuteMsg::UpdateOwnership(action))?)
+        .add_attribute("action", "update_collection_ownership"))
+}
+
+/// Transfers the given amount of the configured payment token or denom.
+fn payment_msg(config: &Config, recipient: String, amount: Uint128) -> StdResult<CosmosMsg> {
+    match (&config.cw20_address, &config.native_denom) {
+        (Some(cw20_address), _) => {
+            Cw20Contract(cw20_address.clone()).call(Cw20ExecuteMsg::Transfer { recipient, amount })
+        }
+        (None, Some(denom)) => Ok(BankMsg::Send {
+            to_address: recipient,
+            amount: coins(amount.u128(), denom),
+        }
+        .into()),
+        (None, None) => Err(StdError::generic_err("no payment route configured")),
+    }
+}
+
+fn is_active(phase: &SalePhase, block: &BlockInfo) -> bool {
+    phase.start.is_expired(block) && !phase.end.is_expired(block)
+}
+
+/// Returns the first active sale phase, `None` if no phases are set.
+fn active_phase(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+) -> Result<Option<SalePhase>, ContractError> {
+    match SALE_PHASES.may_load(storage)? {
+        Some(phases) => phases
+            .into_iter()
+            .find(|phase| is_active(phase, block))
+            .map(Some)
+            .ok_or(ContractError::NoActivePhase {}),
+        None => Ok(None),
+    }
+}
+
+/// Checks supply, payment, wallet limits and allowlist, if any, and mints the requested quantity
+/// of tokens to the buyer. Returns the response and the paid amount exceeding the price.
+/// The allowlist of the active phase replaces the one set by `SetMerkleRoot`.
+fn mint_tokens(
+    deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+    sender: String,
+    amount: Uint128,
+    receive_msg: &ReceiveMsg,
+) -> Result<(Response, Uint128), ContractError> {
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    if RANDOM_SEED.exists(deps.storage) {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+
+    if reveal_expired(deps.storage, &env.block, config)? {
+        return Err(ContractError::RevealDeadlinePassed {});
+    }
+
+    if let Some(auction) = &config.dutch_auction {
+        if env.block.time < auction.start_time {
+            return Err(ContractError::AuctionNotStarted {});
+        }
+    }
+
+    let cw721_address = config
+        .cw721_address
+        .clone()
+        .ok_or(ContractError::Uninitialized {})?;
+
+    let quantity = receive_msg.quantity.unwrap_or(1);
+    if quantity == 0 {
+        return Err(ContractError::InvalidQuantity {});
+    }
+
+    if quantity > config.max_tokens.saturating_sub(config.unused_token_id) {
+        return Err(ContractError::SoldOut {});
+    }
+
+    let phase = active_phase(deps.storage, &env.block)?;
+    let unit_price = current_price(config, phase.as_ref(), &env.block);
+    let price = unit_price
+        .checked_mul(quantity.into())
+        .map_err(StdError::from)?;
+    if amount < price {
+        return Err(ContractError::WrongPaymentAmount {});
+    }
+
+    let buyer = deps.api.addr_validate(&sender)?;
+    let minted_by = MINTED_BY
+        .may_load(deps.storage, &buyer)?
+        .unwrap_or_default();
+    if let Some(max_per_wallet) = config.max_per_wallet {
+        if minted_by + quantity > max_per_wallet {
+            return Err(ContractError::WalletLimitReached {});
+        }
+    }
+    MINTED_BY.save(deps.storage, &buyer, &(minted_by + quantity))?;
+
+    match phase {
+        Some(phase) => {
+            let minted = PHASE_MINTS
+                .may_load(deps.storage, (&phase.name, &buyer))?
+                .unwrap_or_default();
+            if let Some(merkle_root) = &phase.merkle_root {
+                let allowance = verify_allowlist(merkle_root, &sender, receive_msg)?;
+                if minted + quantity > allowance {
+                    return Err(ContractError::AllowanceExceeded {});
+                }
+            }
+            if let Some(max_per_wallet) = phase.max_per_wallet {
+                if minted + quantity > max_per_wallet {
+                    return Err(ContractError::WalletLimitReached {});
+                }
+            }
+            PHASE_MINTS.save(deps.storage, (&phase.name, &buyer), &(minted + quantity))?;
+        }
+        None => {
+            if let Some(merkle_root) = MERKLE_ROOT.may_load(deps.storage)? {
+                let allowance = verify_allowlist(&merkle_root, &sender, receive_msg)?;
+                let minted = ALLOWLIST_MINTS
+                    .may_load(deps.storage, &buyer)?
+                    .unwrap_or_default();
+                if minted + quantity > allowance {
+                    return Err(ContractError::AllowanceExceeded {});
+                }
+                ALLOWLIST_MINTS.save(deps.storage, &buyer, &(minted + quantity))?;
+            }
+        }
+    }
+
+    let cw721 = Cw721Contract::<DefaultOptionMetadataExtension, Empty>(
+        cw721_address,
+        PhantomData,
+        PhantomData,
+    );
+    let mut res = Response::new();
+    for _ in 0..quantity {
+        match config.assignment {
+            // minted once the ids are drawn after the reveal
+            TokenAssignment::Random { .. } => {
+                let reservation = Reservation {
+                    buyer: buyer.clone(),
+                    height: env.block.height,
+                    time: env.block.time,
+                };
+                RESERVATIONS.save(deps.storage, config.unused_token_id, &reservation)?
+            }
+            TokenAssignment::Sequential => {
+                let token_id = config.start_token_id + config.unused_token_id;
+                let mint_msg = mint_msg(deps.storage, config, token_id, sender.clone())?;
+                res = res.add_message(cw721.call(mint_msg)?);
+            }
+        }
+        config.unused_token_id += 1;
+    }
+    CONFIG.save(deps.storage, config)?;
+
+    let mut proceeds = PROCEEDS.may_load(deps.storage)?.unwrap_or_default();
+    proceeds.collected += price;
+    PROCEEDS.save(deps.storage, &proceeds)?;
+
+    if let TokenAssignment::Random { .. } = config.assignment {
+        let refundable = REFUNDABLE
+            .may_load(deps.storage, &buyer)?
+            .unwrap_or_default();
+        REFUNDABLE.save(deps.storage, &buyer, &(refundable + price))?;
+    }
+
+    if let Some(auction) = &config.dutch_auction {
+        LAST_AUCTION_PRICE.save(deps.storage, &unit_price)?;
+        if auction.rebate {
+            let add_deposit = |mut deposit: RebateDeposit| {
+                deposit.paid += price;
+                deposit.quantity += quantity;
+                deposit
+            };
+            let deposit = REBATE_DEPOSITS
+                .may_load(deps.storage, &buyer)?
+                .unwrap_or_default();
+            REBATE_DEPOSITS.save(deps.storage, &buyer, &add_deposit(deposit))?;
+            let total = REBATE_TOTAL.may_load(deps.storage)?.unwrap_or_default();
+            REBATE_TOTAL.save(deps.storage, &add_deposit(total))?;
+        }
+    }
+
+    Ok((res, amount - price))
+}
+
+/// Returns the price of a single token, the auction price replaces the one of the config and phase.
+fn current_price(config: &Config, phase: Option<&SalePhase>, block: &BlockInfo) -> Uint128 {
+    match &config.dutch_auction {
+        Some(auction) => auction.price_at(block.time),
+        None => phase.map_or(config.unit_price, |p| p.unit_price),
+    }
+}
+
+/// Returns the clearing price of a rebate auction once sold out or once the price reached the floor.
+fn clearing_price(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    config: &Config,
+) -> StdResult<Option<Uint128>> {
+    let auction = match &config.dutch_auction {
+        Some(auction) => auction,
+        None => return Ok(None),
+    };
+    if config.unused_token_id >= config.max_tokens {
+        return LAST_AUCTION_PRICE.may_load(storage);
+    }
+    if auction.price_at(block.time) == auction.floor_price {
+        return Ok(Some(auction.floor_price));
+    }
+    Ok(None)
+}
+
+/// Returns the proceeds reserved for unclaimed rebates, assuming the floor price until settled.
+fn reserved_rebates(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    config: &Config,
+) -> StdResult<Uint128> {
+    let auction = match &config.dutch_auction {
+        Some(auction) if auction.rebate => auction,
+        _ => return Ok(Uint128::zero()),
+    };
+    let total = REBATE_TOTAL.may_load(storage)?.unwrap_or_default();
+    let price = clearing_price(storage, block, config)?.unwrap_or(auction.floor_price);
+    Ok(total
+        .paid
+        .saturating_sub(price.saturating_mul(total.quantity.into())))
+}
+
+/// Returns the cw721 mint message of the token, with its uploaded metadata if any.
+fn mint_msg(
+    storage: &dyn Storage,
+    config: &Config,
+    token_id: u32,
+    owner: String,
+) -> StdResult<Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>> {
+    let token_uri = match &config.base_uri {
+        Some(base_uri) => format!("{base_uri}{token_id}"),
+        None => config.token_uri.clone(),
+    };
+    let extension = match TOKEN_METADATA.may_load(storage, token_id)? {
+        Some(metadata) => Some(metadata),
+        None => config.extension.clone(),
+    };
+    Ok(Cw721ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner,
+        token_uri: token_uri.into(),
+        extension,
+    })
+}
+
+/// Returns whether the reveal deadline of the random assignment passed without the secret being revealed.
+fn reveal_expired(storage: &dyn Storage, block: &BlockInfo, config: &Config) -> StdResult<bool> {
+    match &config.assignment {
+        TokenAssignment::Random {
+            reveal_deadline, ..
+        } => Ok(reveal_deadline.is_expired(block) && !RANDOM_SEED.exists(storage)),
+        TokenAssignment::Sequential => Ok(false),
+    }
+}
+
+/// Draws the index of the token id of a reserved slot from the seed, its buyer and purchase block,
+/// removing it from the pool of remaining ids. Slots must be drawn in order.
+fn draw_token_id(
+    storage: &mut dyn Storage,
+    seed: &[u8],
+    config: &Config,
+    slot: u32,
+    reservation: &Reservation,
+) -> StdResult<u32> {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(slot.to_be_bytes());
+    hasher.update(reservation.buyer.as_bytes());
+    hasher.update(reservation.height.to_be_bytes());
+    hasher.update(reservation.time.nanos().to_be_bytes());
+    let mut rand_buf: [u8; 8] = [0; 8];
+    rand_buf.copy_from_slice(&hasher.finalize()[..8]);
+
+    // swap-and-pop: take the picked position and move the last remaining id into it
+    let remaining = config.max_tokens - slot;
+    let index = (u64::from_be_bytes(rand_buf) % u64::from(remaining)) as u32;
+    let last = remaining - 1;
+    let token_id = REMAINING_IDS.may_load(storage, index)?.unwrap_or(index);
+    if index != last {
+        let last_id = REMAINING_IDS.may_load(storage, last)?.unwrap_or(last);
+        REMAINING_IDS.save(storage, index, &last_id)?;
+    }
+    REMAINING_IDS.remove(storage, last);
+    Ok(token_id)
+}
+
+/// Verifies the proof of the sender's leaf against the merkle root, returning the sender's allowance.
+fn verify_allowlist(
+    merkle_root: &str,
+    sender: &str,
+    receive_msg: &ReceiveMsg,
+) -> Result<u32, ContractError> {
+    let allowance = receive_msg
+        .allowance
+        .ok_or(ContractError::InvalidMerkleProof {})?;
+    let leaf: [u8; 32] = Sha256::digest(format!("{sender}:{allowance}").as_bytes()).into();
+    let hash =
+        receive_msg
+            .proof
+            .iter()
+            .try_fold(leaf, |hash, p| -> Result<[u8; 32], ContractError> {
+                let mut proof_buf: [u8; 32] = [0; 32];
+                hex::decode_to_slice(p, &mut proof_buf)
+                    .map_err(|_| ContractError::InvalidMerkleProof {})?;
+                let mut hashes = [hash, proof_buf];
+                hashes.sort_unstable();
+                Ok(Sha256::digest(hashes.concat()).into())
+            })?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    if root_buf != hash {
+        return Err(ContractError::InvalidMerkleProof {});
+    }
+    Ok(allowance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DutchAuction;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{
+        coins, from_json, to_json_binary, ContractResult, CosmosMsg, SubMsgResponse, SubMsgResult,
+        SystemResult, WasmQuery,
+    };
+    use cw721::error::Cw721ContractError;
+    use cw721::state::{DefaultOptionMetadataExtension, Metadata};
+    use cw_utils::PaymentError;
+    use prost::Message;
+    use sha2::{Digest, Sha256};
+
+    const NFT_CONTRACT_ADDR: &str = "nftcontract";
+
+    // Type for replies to contract instantiate messes
+    #[derive(Clone, PartialEq, Message)]
+    struct MsgInstantiateContractResponse {
+        #[prost(string, tag = "1")]
+        pub contract_address: ::prost::alloc::string::String,
+        #[prost(bytes, tag = "2")]
+        pub data: ::prost::alloc::vec::Vec<u8>,
+    }
+
+    fn default_instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            owner: Addr::unchecked("owner"),
+            max_tokens: 1,
+            max_per_wallet: None,
+            unit_price: Uint128::new(1),
             name: String::from("SYNTH"),
             symbol: String::from("SYNTH"),
             token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            cw721_admin: None,
+            cw721_address: None,
+            cw20_address: Some(Addr::unchecked(MOCK_CONTRACT_ADDR)),
+            native_denom: None,
             token_uri: String::from("https://ipfs.io/ipfs/Q"),
+            base_uri: None,
             extension: None,
             withdraw_address: None,
+            snapshot_ownership: None,
+            assignment: None,
+            dutch_auction: None,
+            start_token_id: None,
+        }
+    }
+
+    #[test]
+    fn initialization() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            snapshot_ownership: Some(true),
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+        assert_eq!(
+            res.messages,
+            vec![SubMsg {
+                msg: WasmMsg::Instantiate {
+                    code_id: msg.token_code_id,
+                    msg: to_json_binary(&Cw721InstantiateMsg {
+                        name: msg.name.clone(),
+                        symbol: msg.symbol.clone(),
+                        minter: None,
+                        withdraw_address: None,
+                        snapshot_ownership: Some(true),
+                        clawback_enabled: None,
+                        clear_user_on_transfer: None,
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                    admin: None,
+                    label: String::from("Instantiate fixed price NFT contract"),
+                }
+                .into(),
+                id: INSTANTIATE_TOKEN_REPLY_ID,
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            }]
+        );
+
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: "nftcontract".to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let query_msg = QueryMsg::GetConfig {};
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let config: Config = from_json(res).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                owner: Addr::unchecked("owner"),
+                cw20_address: msg.cw20_address,
+                native_denom: None,
+                cw721_address: Some(Addr::unchecked(NFT_CONTRACT_ADDR)),
+                max_tokens: msg.max_tokens,
+                max_per_wallet: msg.max_per_wallet,
+                unit_price: msg.unit_price,
+                name: msg.name,
+                symbol: msg.symbol,
+                token_uri: msg.token_uri,
+                base_uri: None,
+                extension: None,
+                unused_token_id: 0,
+                start_token_id: 0,
+                assignment: TokenAssignment::Sequential,
+                dutch_auction: None,
+                paused: false,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_unit_price() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            unit_price: Uint128::new(0),
+            ..default_instantiate_msg()
         };
 
         let info = mock_info("owner", &[]);
@@ -314,16 +1461,8 @@ mod tests {
     fn invalid_max_tokens() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
             max_tokens: 0,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+            ..default_instantiate_msg()
         };
 
         let info = mock_info("owner", &[]);
@@ -338,18 +1477,7 @@ mod tests {
     #[test]
     fn mint() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
-        };
+        let msg = default_instantiate_msg();
 
         let info = mock_info("owner", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -404,20 +1532,218 @@ mod tests {
     }
 
     #[test]
-    fn invalid_reply_id() {
+    fn mint_quantity() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
+            max_tokens: 5,
+            unit_price: Uint128::new(10),
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let receive_msg = |quantity: u32, amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("minter"),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg {
+                    quantity: Some(quantity),
+                    ..ReceiveMsg::default()
+                })
+                .unwrap(),
+            })
+        };
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), receive_msg(0, 10)).unwrap_err();
+        match err {
+            ContractError::InvalidQuantity {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // 3 tokens cost 30, excess of 5 is refunded
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), receive_msg(3, 35)).unwrap();
+        assert_eq!(res.messages.len(), 4);
+        for (i, sub_msg) in res.messages[..3].iter().enumerate() {
+            let mint_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::Mint {
+                token_id: i.to_string(),
+                owner: String::from("minter"),
+                token_uri: Some(String::from("https://ipfs.io/ipfs/Q")),
+                extension: None,
+            };
+            assert_eq!(
+                sub_msg.msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                    msg: to_json_binary(&mint_msg).unwrap(),
+                    funds: vec![],
+                })
+            );
+        }
+        assert_eq!(
+            res.messages[3].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("minter"),
+                    amount: Uint128::new(5),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // only 2 tokens are left
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), receive_msg(3, 30)).unwrap_err();
+        match err {
+            ContractError::SoldOut {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // exact payment needs no refund
+        let res = execute(deps.as_mut(), mock_env(), info, receive_msg(2, 20)).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.unused_token_id, 5);
+    }
+
+    #[test]
+    fn mint_native() {
+        let mut deps = mock_dependencies();
+        let mut msg = InstantiateMsg {
+            max_tokens: 2,
+            native_denom: Some(String::from("ujuno")),
+            ..default_instantiate_msg()
+        };
+
+        // both payment routes configured
+        let info = mock_info("owner", &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::InvalidPaymentConfig {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        msg.cw20_address = None;
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let config: ConfigResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.native_denom, Some(String::from("ujuno")));
+        assert_eq!(config.cw20_address, None);
+
+        // cw20 payments are rejected
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::UnauthorizedTokenContract {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let msg = ExecuteMsg::Mint {
+            quantity: None,
+            allowance: None,
+            proof: None,
+        };
+
+        // wrong denom
+        let info = mock_info("minter", &coins(1, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::Payment(PaymentError::MissingDenom(_)) => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // not enough funds for the quantity
+        let info = mock_info("minter", &coins(1, "ujuno"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Mint {
+                quantity: Some(2),
+                allowance: None,
+                proof: None,
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::WrongPaymentAmount {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let info = mock_info("minter", &coins(1, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mint_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::Mint {
+            token_id: String::from("0"),
+            owner: String::from("minter"),
+            token_uri: Some(String::from("https://ipfs.io/ipfs/Q")),
             extension: None,
-            withdraw_address: None,
         };
+        assert_eq!(
+            res.messages[0],
+            SubMsg {
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                    msg: to_json_binary(&mint_msg).unwrap(),
+                    funds: vec![],
+                }),
+                id: 0,
+                gas_limit: None,
+                reply_on: ReplyOn::Never,
+            }
+        );
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.unused_token_id, 1);
+    }
+
+    #[test]
+    fn invalid_reply_id() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate_msg();
 
         let info = mock_info("owner", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -447,22 +1773,997 @@ mod tests {
 
     #[test]
     fn cw721_already_linked() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate_msg();
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        match err {
+            ContractError::Cw721AlreadyLinked {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn sold_out() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate_msg();
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // Max mint is 1, so second mint request should fail
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+        match err {
+            ContractError::SoldOut {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn wallet_limit() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+            max_tokens: 3,
+            max_per_wallet: Some(1),
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // Max per wallet is 1, so second mint request of the same buyer should fail
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        match err {
+            ContractError::WalletLimitReached {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // other buyers are not affected
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("other"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res: MintedByResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::MintedBy {
+                    address: String::from("minter"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.count, 1);
+        let res: MintedByResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::MintedBy {
+                    address: String::from("buyer"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.count, 0);
+    }
+
+    #[test]
+    fn uninitialized() {
+        // Config has not been fully initialized with nft contract address via instantiation reply
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate_msg();
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Test token transfer when nft contract has not been linked
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::Uninitialized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn unauthorized_token() {
+        let mut deps = mock_dependencies();
+        let msg = default_instantiate_msg();
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Link nft token contract using reply
+
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        // Test token transfer from invalid token contract
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(1),
+            msg: [].into(),
+        });
+        let info = mock_info("unauthorized-token", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+        match err {
+            ContractError::UnauthorizedTokenContract {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn wrong_amount() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            unit_price: Uint128::new(200),
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Link nft token contract using reply
+
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        // Test token transfer from invalid token contract
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(100),
+            msg: [].into(),
+        });
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+        match err {
+            ContractError::WrongPaymentAmount {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn allowlist() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_tokens: 3,
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        // allowlist of two leaves, "minter" may mint 2 tokens and "other" 1 token
+        let minter_leaf: [u8; 32] = Sha256::digest(b"minter:2").into();
+        let other_leaf: [u8; 32] = Sha256::digest(b"other:1").into();
+        let mut leaves = [minter_leaf, other_leaf];
+        leaves.sort_unstable();
+        let merkle_root = hex::encode(Sha256::digest(leaves.concat()));
+
+        // only owner can set the merkle root
+        let set_root_msg = ExecuteMsg::SetMerkleRoot {
+            merkle_root: Some(merkle_root.clone()),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            set_root_msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetMerkleRoot {
+                merkle_root: Some("0xinvalid".to_string()),
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidMerkleRoot {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_root_msg,
+        )
+        .unwrap();
+        let res: MerkleRootResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoot {}).unwrap()).unwrap();
+        assert_eq!(res.merkle_root, Some(merkle_root));
+
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let receive_msg = |allowance: u32, proof: &[u8; 32]| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("minter"),
+                amount: Uint128::new(1),
+                msg: to_json_binary(&ReceiveMsg {
+                    quantity: None,
+                    allowance: Some(allowance),
+                    proof: vec![hex::encode(proof)],
+                })
+                .unwrap(),
+            })
+        };
+
+        // wallets without proof can't mint
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("minter"),
+                amount: Uint128::new(1),
+                msg: [].into(),
+            }),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidMerkleProof {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // allowance must match the leaf
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            receive_msg(3, &other_leaf),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidMerkleProof {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // allowlisted wallet mints up to its allowance
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            receive_msg(2, &other_leaf),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            receive_msg(2, &other_leaf),
+        )
+        .unwrap();
+        let err =
+            execute(deps.as_mut(), mock_env(), info, receive_msg(2, &other_leaf)).unwrap_err();
+        match err {
+            ContractError::AllowanceExceeded {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn sale_phases() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_tokens: 5,
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let presale = SalePhase {
+            name: String::from("presale"),
+            start: Expiration::AtHeight(100),
+            end: Expiration::AtHeight(200),
+            unit_price: Uint128::new(1),
+            merkle_root: None,
+            max_per_wallet: Some(1),
+        };
+        let public = SalePhase {
+            name: String::from("public"),
+            start: Expiration::AtHeight(200),
+            end: Expiration::AtHeight(300),
+            unit_price: Uint128::new(2),
+            merkle_root: None,
+            max_per_wallet: None,
+        };
+
+        // only owner can set phases
+        let set_phases_msg = ExecuteMsg::SetSalePhases {
+            phases: vec![presale.clone(), public.clone()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            set_phases_msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // phase must start before it ends
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetSalePhases {
+                phases: vec![SalePhase {
+                    start: Expiration::AtHeight(200),
+                    end: Expiration::AtHeight(100),
+                    ..presale.clone()
+                }],
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidSalePhase { name } => assert_eq!(name, "presale"),
+            e => panic!("unexpected error: {e}"),
+        }
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            set_phases_msg,
+        )
+        .unwrap();
+
+        let env_at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let current_phase = |deps: Deps, height: u64| -> Option<SalePhase> {
+            let res: CurrentPhaseResponse =
+                from_json(query(deps, env_at(height), QueryMsg::CurrentPhase {}).unwrap()).unwrap();
+            res.phase
+        };
+        let receive_msg = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("minter"),
+                amount: Uint128::new(amount),
+                msg: [].into(),
+            })
+        };
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // sale has not started yet
+        assert_eq!(current_phase(deps.as_ref(), 50), None);
+        let err = execute(deps.as_mut(), env_at(50), info.clone(), receive_msg(1)).unwrap_err();
+        match err {
+            ContractError::NoActivePhase {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // presale is limited to one token per wallet
+        assert_eq!(current_phase(deps.as_ref(), 150), Some(presale));
+        execute(deps.as_mut(), env_at(150), info.clone(), receive_msg(1)).unwrap();
+        let err = execute(deps.as_mut(), env_at(150), info.clone(), receive_msg(1)).unwrap_err();
+        match err {
+            ContractError::WalletLimitReached {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // public sale has its own price
+        assert_eq!(current_phase(deps.as_ref(), 250), Some(public));
+        let err = execute(deps.as_mut(), env_at(250), info.clone(), receive_msg(1)).unwrap_err();
+        match err {
+            ContractError::WrongPaymentAmount {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(deps.as_mut(), env_at(250), info.clone(), receive_msg(2)).unwrap();
+        execute(deps.as_mut(), env_at(250), info.clone(), receive_msg(2)).unwrap();
+
+        // sale is closed after the last phase
+        assert_eq!(current_phase(deps.as_ref(), 300), None);
+        let err = execute(deps.as_mut(), env_at(300), info, receive_msg(2)).unwrap_err();
+        match err {
+            ContractError::NoActivePhase {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.unused_token_id, 3);
+    }
+
+    #[test]
+    fn withdraw_proceeds() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_tokens: 5,
+            unit_price: Uint128::new(10),
+            ..default_instantiate_msg()
+        };
+
+        let info = mock_info("owner", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        // 3 tokens for 30, excess of 5 is refunded and not collected
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("minter"),
+            amount: Uint128::new(35),
+            msg: to_json_binary(&ReceiveMsg {
+                quantity: Some(3),
+                ..ReceiveMsg::default()
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            msg,
+        )
+        .unwrap();
+        let proceeds = |deps: Deps| -> ProceedsResponse {
+            from_json(query(deps, mock_env(), QueryMsg::Proceeds {}).unwrap()).unwrap()
+        };
+        assert_eq!(
+            proceeds(deps.as_ref()),
+            ProceedsResponse {
+                collected: Uint128::new(30),
+                withdrawn: Uint128::zero(),
+            }
+        );
+        let transfer = |recipient: &str, amount: u128| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+
+        // only owner can withdraw
+        let withdraw_msg =
+            |amount: Option<u128>, recipient: Option<&str>| ExecuteMsg::WithdrawProceeds {
+                amount: amount.map(Uint128::new),
+                recipient: recipient.map(String::from),
+            };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            withdraw_msg(None, None),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg(Some(40), None),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientProceeds {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg(Some(10), Some("treasury")),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, transfer("treasury", 10));
+
+        // shares must add up to 100%
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetPayees {
+                payees: vec![Payee {
+                    address: Addr::unchecked("alice"),
+                    bps: 9000,
+                }],
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidPayees {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::SetPayees {
+                payees: vec![
+                    Payee {
+                        address: Addr::unchecked("alice"),
+                        bps: 3333,
+                    },
+                    Payee {
+                        address: Addr::unchecked("bob"),
+                        bps: 6667,
+                    },
+                ],
+            },
+        )
+        .unwrap();
+
+        // remaining 20 is split, last payee gets the rounding remainder
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg(None, None),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].msg, transfer("alice", 6));
+        assert_eq!(res.messages[1].msg, transfer("bob", 14));
+        assert_eq!(
+            proceeds(deps.as_ref()),
+            ProceedsResponse {
+                collected: Uint128::new(30),
+                withdrawn: Uint128::new(30),
+            }
+        );
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg(None, None),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientProceeds {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn random_assignment() {
+        let mut deps = mock_dependencies();
+        let mut msg = InstantiateMsg {
+            max_tokens: 5,
+            assignment: Some(TokenAssignment::Random {
+                commitment: String::from("invalid"),
+                reveal_deadline: Expiration::AtHeight(mock_env().block.height + 100),
+            }),
+            ..default_instantiate_msg()
+        };
+
+        // commitment must be a hex encoded sha256 hash
+        let info = mock_info("owner", &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::InvalidCommitment {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // buyers must be able to get refunded at some point
+        for reveal_deadline in [
+            Expiration::Never {},
+            Expiration::AtHeight(mock_env().block.height),
+        ] {
+            msg.assignment = Some(TokenAssignment::Random {
+                commitment: hex::encode(Sha256::digest(b"secret")),
+                reveal_deadline,
+            });
+            let err =
+                instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+            match err {
+                ContractError::InvalidRevealDeadline {} => {}
+                e => panic!("unexpected error: {e}"),
+            }
+        }
+
+        msg.assignment = Some(TokenAssignment::Random {
+            commitment: hex::encode(Sha256::digest(b"secret")),
+            reveal_deadline: Expiration::AtHeight(mock_env().block.height + 100),
+        });
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let instantiate_reply = MsgInstantiateContractResponse {
+            contract_address: NFT_CONTRACT_ADDR.to_string(),
+            data: vec![2u8; 32769],
+        };
+        let mut encoded_instantiate_reply =
+            Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+        instantiate_reply
+            .encode(&mut encoded_instantiate_reply)
+            .unwrap();
+
+        let reply_msg = Reply {
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(encoded_instantiate_reply.into()),
+            }),
+        };
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        let receive_msg = |quantity: u32| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("minter"),
+                amount: Uint128::new(quantity.into()),
+                msg: to_json_binary(&ReceiveMsg {
+                    quantity: Some(quantity),
+                    ..ReceiveMsg::default()
+                })
+                .unwrap(),
+            })
+        };
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // sold tokens are reserved, not minted
+        for quantity in [2, 3] {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                receive_msg(quantity),
+            )
+            .unwrap();
+            assert!(res.messages.is_empty());
+        }
+        let res: DistributionResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Distribution {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            res,
+            DistributionResponse {
+                revealed: false,
+                reserved: 5,
+                distributed: 0,
+            }
+        );
+
+        let distribute_msg = |limit: Option<u32>| ExecuteMsg::DistributeTokens { limit };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            distribute_msg(None),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotRevealed {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // proceeds are refundable until the reveal
+        let withdraw_msg = ExecuteMsg::WithdrawProceeds {
+            amount: None,
+            recipient: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientProceeds {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // only owner can reveal, the secret must match the commitment
+        let reveal_msg = |secret: &str| ExecuteMsg::RevealSecret {
+            secret: String::from(secret),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            reveal_msg("secret"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            reveal_msg("guess"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidSecret {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            reveal_msg("secret"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            reveal_msg("secret"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AlreadyRevealed {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // the reveal ends the sale
+        let err = execute(deps.as_mut(), mock_env(), info, receive_msg(1)).unwrap_err();
+        match err {
+            ContractError::AlreadyRevealed {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let res: SaleStatusResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::SaleStatus {}).unwrap()).unwrap();
+        assert!(!res.open);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            withdraw_msg,
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // anyone can distribute, each remaining id is assigned exactly once
+        let mut token_ids = vec![];
+        for (limit, minted) in [(Some(2), 2), (None, 3), (None, 0)] {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("anyone", &[]),
+                distribute_msg(limit),
+            )
+            .unwrap();
+            assert_eq!(res.messages.len(), minted);
+            for sub_msg in res.messages {
+                let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = sub_msg.msg else {
+                    panic!("unexpected message");
+                };
+                let mint_msg: Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty> =
+                    from_json(msg).unwrap();
+                match mint_msg {
+                    Cw721ExecuteMsg::Mint {
+                        token_id, owner, ..
+                    } => {
+                        assert_eq!(owner, "minter");
+                        token_ids.push(token_id.parse::<u32>().unwrap())
+                    }
+                    _ => panic!("unexpected message"),
+                }
+            }
+        }
+        token_ids.sort_unstable();
+        assert_eq!(token_ids, vec![0, 1, 2, 3, 4]);
+        assert!(REMAINING_IDS
+            .keys(
+                deps.as_ref().storage,
+                None,
+                None,
+                cosmwasm_std::Order::Ascending
+            )
+            .next()
+            .is_none());
+        let res: DistributionResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Distribution {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            res,
+            DistributionResponse {
+                revealed: true,
+                reserved: 5,
+                distributed: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn random_assignment_refund() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            max_tokens: 5,
+            unit_price: Uint128::new(10),
+            assignment: Some(TokenAssignment::Random {
+                commitment: hex::encode(Sha256::digest(b"secret")),
+                reveal_deadline: Expiration::AtHeight(mock_env().block.height + 100),
+            }),
+            ..default_instantiate_msg()
         };
-
-        let info = mock_info("owner", &[]);
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
         let instantiate_reply = MsgInstantiateContractResponse {
             contract_address: NFT_CONTRACT_ADDR.to_string(),
             data: vec![2u8; 32769],
@@ -474,35 +2775,119 @@ mod tests {
             .unwrap();
 
         let reply_msg = Reply {
-            id: 1,
+            id: INSTANTIATE_TOKEN_REPLY_ID,
             result: SubMsgResult::Ok(SubMsgResponse {
                 events: vec![],
                 data: Some(encoded_instantiate_reply.into()),
             }),
         };
-        reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        let receive_msg = |sender: &str, quantity: u32| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from(sender),
+                amount: Uint128::new(u128::from(quantity) * 10),
+                msg: to_json_binary(&ReceiveMsg {
+                    quantity: Some(quantity),
+                    ..ReceiveMsg::default()
+                })
+                .unwrap(),
+            })
+        };
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        for (sender, quantity) in [("alice", 2), ("bob", 1), ("alice", 1)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                receive_msg(sender, quantity),
+            )
+            .unwrap();
+        }
+
+        // no refunds before the deadline
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::ClaimRefund {},
+        )
+        .unwrap_err();
         match err {
-            ContractError::Cw721AlreadyLinked {} => {}
+            ContractError::RefundUnavailable {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // the sale ends once the deadline passed without a reveal
+        let mut env = mock_env();
+        env.block.height += 100;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            ExecuteMsg::RevealSecret {
+                secret: String::from("secret"),
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::RevealDeadlinePassed {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let err = execute(deps.as_mut(), env.clone(), info, receive_msg("carol", 1)).unwrap_err();
+        match err {
+            ContractError::RevealDeadlinePassed {} => {}
             e => panic!("unexpected error: {e}"),
         }
+        let res: SaleStatusResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::SaleStatus {}).unwrap()).unwrap();
+        assert!(!res.open);
+
+        // buyers get back all they paid, once
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            ExecuteMsg::ClaimRefund {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("alice"),
+                    amount: Uint128::new(30),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+        for sender in ["alice", "carol"] {
+            let err = execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(sender, &[]),
+                ExecuteMsg::ClaimRefund {},
+            )
+            .unwrap_err();
+            match err {
+                ContractError::NoRefund {} => {}
+                e => panic!("unexpected error: {e}"),
+            }
+        }
+        let res: ProceedsResponse =
+            from_json(query(deps.as_ref(), env, QueryMsg::Proceeds {}).unwrap()).unwrap();
+        assert_eq!(res.collected, Uint128::new(10));
     }
 
     #[test]
-    fn sold_out() {
+    fn token_metadata() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+            max_tokens: 2,
+            base_uri: Some(String::from("ipfs://base/")),
+            ..default_instantiate_msg()
         };
 
         let info = mock_info("owner", &[]);
@@ -525,81 +2910,205 @@ mod tests {
             }),
         };
         reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-            sender: String::from("minter"),
-            amount: Uint128::new(1),
-            msg: [].into(),
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&MetadataValidation::default()).unwrap(),
+            ))
         });
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
 
-        // Max mint is 1, so second mint request should fail
-        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        let entry = |token_id: u32| TokenMetadata {
+            token_id,
+            metadata: Metadata {
+                name: Some(format!("SYNTH #{token_id}")),
+                ..Metadata::default()
+            },
+        };
+        let upload_msg = |token_id: u32| ExecuteMsg::UploadMetadata {
+            entries: vec![entry(token_id)],
+        };
 
+        // only owner can upload
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            upload_msg(0),
+        )
+        .unwrap_err();
         match err {
-            ContractError::SoldOut {} => {}
+            ContractError::Unauthorized {} => {}
             e => panic!("unexpected error: {e}"),
         }
-    }
 
-    #[test]
-    fn uninitialized() {
-        // Config has not been fully initialized with nft contract address via instantiation reply
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            upload_msg(2),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidTokenId { token_id } => assert_eq!(token_id, 2),
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // metadata must pass the validation of the collection
+        let http_upload_msg = ExecuteMsg::UploadMetadata {
+            entries: vec![TokenMetadata {
+                token_id: 0,
+                metadata: Metadata {
+                    image: Some(String::from("http://example.com/0.png")),
+                    ..Metadata::default()
+                },
+            }],
         };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            http_upload_msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Cw721(Cw721ContractError::UrlSchemeNotAllowed { field, scheme }) => {
+                assert_eq!(field, "image");
+                assert_eq!(scheme, "http");
+            }
+            e => panic!("unexpected error: {e}"),
+        }
 
-        let info = mock_info("owner", &[]);
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        // default validation applies if the collection doesn't support the query
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Err(String::from(
+                "unknown variant `get_metadata_validation`",
+            )))
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            http_upload_msg,
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Cw721(Cw721ContractError::UrlSchemeNotAllowed { field, scheme }) => {
+                assert_eq!(field, "image");
+                assert_eq!(scheme, "http");
+            }
+            e => panic!("unexpected error: {e}"),
+        }
 
-        // Test token transfer when nft contract has not been linked
+        // upload in chunks
+        for token_id in [0, 1] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("owner", &[]),
+                upload_msg(token_id),
+            )
+            .unwrap();
+        }
+        let res: TokenMetadataResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TokenMetadata {
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.entries, vec![entry(0)]);
+        let res: TokenMetadataResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TokenMetadata {
+                    start_after: Some(0),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.entries, vec![entry(1)]);
 
+        // minted token carries its own uri and metadata
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: String::from("minter"),
             amount: Uint128::new(1),
             msg: [].into(),
         });
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            msg,
+        )
+        .unwrap();
+        let mint_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::Mint {
+            token_id: String::from("0"),
+            owner: String::from("minter"),
+            token_uri: Some(String::from("ipfs://base/0")),
+            extension: Some(entry(0).metadata),
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&mint_msg).unwrap(),
+                funds: vec![],
+            })
+        );
 
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        // metadata is locked once the sale started
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            upload_msg(1),
+        )
+        .unwrap_err();
         match err {
-            ContractError::Uninitialized {} => {}
+            ContractError::MetadataLocked {} => {}
             e => panic!("unexpected error: {e}"),
         }
     }
 
     #[test]
-    fn unauthorized_token() {
+    fn dutch_auction() {
         let mut deps = mock_dependencies();
-        let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+        let auction = DutchAuction {
+            start_time: mock_env().block.time,
+            start_price: Uint128::new(100),
+            floor_price: Uint128::new(40),
+            price_step: Uint128::new(10),
+            interval: 60,
+            rebate: true,
+        };
+        // unit price is unused
+        let mut msg = InstantiateMsg {
+            max_tokens: 3,
+            unit_price: Uint128::zero(),
+            dutch_auction: Some(DutchAuction {
+                floor_price: Uint128::new(200),
+                ..auction.clone()
+            }),
+            ..default_instantiate_msg()
         };
 
+        // floor price must not exceed start price
         let info = mock_info("owner", &[]);
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        // Link nft token contract using reply
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::InvalidDutchAuction {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
 
+        msg.dutch_auction = Some(auction);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         let instantiate_reply = MsgInstantiateContractResponse {
             contract_address: NFT_CONTRACT_ADDR.to_string(),
             data: vec![2u8; 32769],
@@ -619,42 +3128,371 @@ mod tests {
         };
         reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-        // Test token transfer from invalid token contract
+        let env_at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let current_price = |deps: Deps, seconds: u64| -> Uint128 {
+            let res: CurrentPriceResponse =
+                from_json(query(deps, env_at(seconds), QueryMsg::CurrentPrice {}).unwrap())
+                    .unwrap();
+            res.price
+        };
+        let receive_msg = |sender: &str, amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: Uint128::new(amount),
+                msg: [].into(),
+            })
+        };
+        let transfer = |recipient: &str, amount: u128| {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+
+        // sale opens at the start time
+        let mut env_before = mock_env();
+        env_before.block.time = env_before.block.time.minus_seconds(1);
+        let err = execute(
+            deps.as_mut(),
+            env_before.clone(),
+            info.clone(),
+            receive_msg("alice", 100),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AuctionNotStarted {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let sale_open = |deps: Deps, env: Env| -> bool {
+            let res: SaleStatusResponse =
+                from_json(query(deps, env, QueryMsg::SaleStatus {}).unwrap()).unwrap();
+            res.open
+        };
+        assert!(!sale_open(deps.as_ref(), env_before));
+        assert!(sale_open(deps.as_ref(), env_at(0)));
+
+        // phases don't need a unit price either
+        let phase = SalePhase {
+            name: String::from("public"),
+            start: Expiration::AtTime(mock_env().block.time),
+            end: Expiration::Never {},
+            unit_price: Uint128::zero(),
+            merkle_root: None,
+            max_per_wallet: None,
+        };
+        for phases in [vec![phase], vec![]] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("owner", &[]),
+                ExecuteMsg::SetSalePhases { phases },
+            )
+            .unwrap();
+        }
+
+        // price decays by one step per interval down to the floor
+        assert_eq!(current_price(deps.as_ref(), 0), Uint128::new(100));
+        assert_eq!(current_price(deps.as_ref(), 125), Uint128::new(80));
+        assert_eq!(current_price(deps.as_ref(), 3600), Uint128::new(40));
+
+        execute(
+            deps.as_mut(),
+            env_at(0),
+            info.clone(),
+            receive_msg("alice", 100),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env_at(125),
+            info.clone(),
+            receive_msg("bob", 70),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::WrongPaymentAmount {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(
+            deps.as_mut(),
+            env_at(125),
+            info.clone(),
+            receive_msg("bob", 80),
+        )
+        .unwrap();
+
+        // no rebates before the auction is settled
+        let err = execute(
+            deps.as_mut(),
+            env_at(125),
+            mock_info("alice", &[]),
+            ExecuteMsg::ClaimRebate {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AuctionNotSettled {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // proceeds above the floor price are reserved for rebates
+        let res = execute(
+            deps.as_mut(),
+            env_at(125),
+            mock_info("owner", &[]),
+            ExecuteMsg::WithdrawProceeds {
+                amount: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages[0].msg, transfer("owner", 80));
+
+        // last token sells at 70, excess is refunded
+        let res = execute(deps.as_mut(), env_at(190), info, receive_msg("alice", 75)).unwrap();
+        assert_eq!(res.messages[1].msg, transfer("alice", 5));
+
+        let res: RebateResponse = from_json(
+            query(
+                deps.as_ref(),
+                env_at(190),
+                QueryMsg::Rebate {
+                    address: String::from("alice"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res,
+            RebateResponse {
+                paid: Uint128::new(170),
+                quantity: 2,
+                claimable: Uint128::new(30),
+            }
+        );
+
+        // sold out, buyers are refunded down to the clearing price of 70
+        for (buyer, rebate) in [("alice", 30), ("bob", 10)] {
+            let res = execute(
+                deps.as_mut(),
+                env_at(190),
+                mock_info(buyer, &[]),
+                ExecuteMsg::ClaimRebate {},
+            )
+            .unwrap();
+            assert_eq!(res.messages[0].msg, transfer(buyer, rebate));
+        }
+        let err = execute(
+            deps.as_mut(),
+            env_at(190),
+            mock_info("alice", &[]),
+            ExecuteMsg::ClaimRebate {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoRebate {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // rest of the proceeds are available
+        let res = execute(
+            deps.as_mut(),
+            env_at(190),
+            mock_info("owner", &[]),
+            ExecuteMsg::WithdrawProceeds {
+                amount: None,
+                recipient: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.messages[0].msg, transfer("owner", 130));
+    }
+
+    #[test]
+    fn existing_collection() {
+        let msg = InstantiateMsg {
+            cw721_admin: Some(String::from("admin")),
+            ..default_instantiate_msg()
+        };
+
+        // new collection is instantiated with the given admin
+        let mut deps = mock_dependencies();
+        let info = mock_info("owner", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate { admin, .. }) => {
+                assert_eq!(admin, &Some(String::from("admin")))
+            }
+            m => panic!("unexpected message: {m:?}"),
+        }
+
+        // admin of an existing collection can't be set
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            cw721_address: Some(NFT_CONTRACT_ADDR.to_string()),
+            ..msg
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::InvalidCw721Admin {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // collection already has tokens 0 and 1
+        let msg = InstantiateMsg {
+            cw721_admin: None,
+            start_token_id: Some(2),
+            ..msg
+        };
+        let mock_ownership = |owner: &str, pending_owner: Option<&str>| {
+            let mut deps = mock_dependencies();
+            let ownership = Ownership {
+                owner: Some(Addr::unchecked(owner)),
+                pending_owner: pending_owner.map(Addr::unchecked),
+                pending_expiry: None,
+            };
+            deps.querier.update_wasm(move |query| {
+                let WasmQuery::Smart { msg, .. } = query else {
+                    panic!("unexpected query");
+                };
+                let res = match from_json(msg).unwrap() {
+                    Cw721QueryMsg::<DefaultOptionMetadataExtension, Empty>::Ownership {} => {
+                        to_json_binary(&ownership)
+                    }
+                    Cw721QueryMsg::ContractInfo {} => to_json_binary(&CollectionInfo {
+                        name: String::from("Existing"),
+                        symbol: String::from("EXT"),
+                    }),
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            });
+            deps
+        };
+
+        // sale contract must be the minter
+        let mut deps = mock_ownership("creator", None);
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::NotMinter {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // pending minter ownership is accepted
+        let mut deps = mock_ownership("creator", Some(MOCK_CONTRACT_ADDR));
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let accept_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::UpdateOwnership(
+            Action::AcceptOwnership,
+        );
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&accept_msg).unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // collection is linked without instantiation
+        let mut deps = mock_ownership(MOCK_CONTRACT_ADDR, None);
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            config.cw721_address,
+            Some(Addr::unchecked(NFT_CONTRACT_ADDR))
+        );
+        assert_eq!(config.name, "Existing");
+        assert_eq!(config.symbol, "EXT");
+
+        // token ids start past the existing ones
         let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: String::from("minter"),
             amount: Uint128::new(1),
             msg: [].into(),
         });
-        let info = mock_info("unauthorized-token", &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            msg,
+        )
+        .unwrap();
+        let mint_msg = Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::Mint {
+            token_id: String::from("2"),
+            owner: String::from("minter"),
+            token_uri: Some(String::from("https://ipfs.io/ipfs/Q")),
+            extension: None,
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&mint_msg).unwrap(),
+                funds: vec![],
+            })]
+        );
 
+        // only owner can hand the minter ownership back to the creator
+        let transfer_action = Action::TransferOwnership {
+            new_owner: String::from("creator"),
+            expiry: None,
+        };
+        let update_msg = ExecuteMsg::UpdateCollectionOwnership {
+            action: transfer_action.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            update_msg.clone(),
+        )
+        .unwrap_err();
         match err {
-            ContractError::UnauthorizedTokenContract {} => {}
+            ContractError::Unauthorized {} => {}
             e => panic!("unexpected error: {e}"),
         }
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            update_msg,
+        )
+        .unwrap();
+        let transfer_msg =
+            Cw721ExecuteMsg::<DefaultOptionMetadataExtension, Empty>::UpdateOwnership(
+                transfer_action,
+            );
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: NFT_CONTRACT_ADDR.to_string(),
+                msg: to_json_binary(&transfer_msg).unwrap(),
+                funds: vec![],
+            })]
+        );
     }
 
     #[test]
-    fn wrong_amount() {
+    fn admin_controls() {
         let mut deps = mock_dependencies();
         let msg = InstantiateMsg {
-            owner: Addr::unchecked("owner"),
-            max_tokens: 1,
-            unit_price: Uint128::new(1),
-            name: String::from("SYNTH"),
-            symbol: String::from("SYNTH"),
-            token_code_id: 10u64,
-            cw20_address: Addr::unchecked(MOCK_CONTRACT_ADDR),
-            token_uri: String::from("https://ipfs.io/ipfs/Q"),
-            extension: None,
-            withdraw_address: None,
+            max_tokens: 4,
+            ..default_instantiate_msg()
         };
 
         let info = mock_info("owner", &[]);
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        // Link nft token contract using reply
-
         let instantiate_reply = MsgInstantiateContractResponse {
             contract_address: NFT_CONTRACT_ADDR.to_string(),
             data: vec![2u8; 32769],
@@ -674,18 +3512,169 @@ mod tests {
         };
         reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-        // Test token transfer from invalid token contract
-        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        let update_msg = |max_tokens: Option<u32>| ExecuteMsg::UpdateConfig {
+            unit_price: Some(Uint128::new(5)),
+            max_tokens,
+            token_uri: Some(String::from("ipfs://new")),
+        };
+        let sale_status = |deps: Deps| -> SaleStatusResponse {
+            from_json(query(deps, mock_env(), QueryMsg::SaleStatus {}).unwrap()).unwrap()
+        };
+
+        // only owner can update config
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            update_msg(None),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // max tokens can only be decreased
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            update_msg(Some(10)),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidMaxTokens {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            update_msg(Some(3)),
+        )
+        .unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.unit_price, Uint128::new(5));
+        assert_eq!(config.max_tokens, 3);
+        assert_eq!(config.token_uri, "ipfs://new");
+
+        // minting is stopped while paused
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
             sender: String::from("minter"),
-            amount: Uint128::new(100),
+            amount: Uint128::new(5),
             msg: [].into(),
         });
         let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), receive_msg.clone()).unwrap_err();
+        match err {
+            ContractError::Paused {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        let status = sale_status(deps.as_ref());
+        assert!(!status.open);
+        assert!(status.paused);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::Resume {},
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), info, receive_msg).unwrap();
+        assert_eq!(
+            sale_status(deps.as_ref()),
+            SaleStatusResponse {
+                open: true,
+                paused: false,
+                minted: 1,
+                max_tokens: 3,
+                phase: None,
+                price: Uint128::new(5),
+            }
+        );
 
+        // ownership is transferred once accepted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::TransferOwnership {
+                new_owner: String::from("newowner"),
+            },
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
         match err {
-            ContractError::WrongPaymentAmount {} => {}
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {e}"),
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("newowner", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+        let ownership: OwnershipResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Ownership {}).unwrap()).unwrap();
+        assert_eq!(
+            ownership,
+            OwnershipResponse {
+                owner: Addr::unchecked("newowner"),
+                pending_owner: None,
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
             e => panic!("unexpected error: {e}"),
         }
     }
+
+    #[test]
+    fn migration() {
+        let mut deps = mock_dependencies();
+
+        // only migrate from this contract
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+
+        // only migrate from an older version
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "migrate"),
+                ("from_version", "0.1.0"),
+                ("to_version", CONTRACT_VERSION),
+            ]
+        );
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
 }
@@ -1,4 +1,6 @@
 use cosmwasm_std::StdError;
+use cw721::error::Cw721ContractError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +8,12 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Cw721(#[from] Cw721ContractError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -15,9 +23,54 @@ pub enum ContractError {
     #[error("InvalidMaxTokens")]
     InvalidMaxTokens {},
 
+    #[error("InvalidMaxPerWallet")]
+    InvalidMaxPerWallet {},
+
+    #[error("InvalidPaymentConfig")]
+    InvalidPaymentConfig {},
+
+    #[error("InvalidCw721Admin")]
+    InvalidCw721Admin {},
+
+    #[error("InvalidCommitment")]
+    InvalidCommitment {},
+
+    #[error("InvalidSecret")]
+    InvalidSecret {},
+
+    #[error("AlreadyRevealed")]
+    AlreadyRevealed {},
+
+    #[error("NotRevealed")]
+    NotRevealed {},
+
+    #[error("InvalidRevealDeadline")]
+    InvalidRevealDeadline {},
+
+    #[error("RevealDeadlinePassed")]
+    RevealDeadlinePassed {},
+
+    #[error("RefundUnavailable")]
+    RefundUnavailable {},
+
+    #[error("NoRefund")]
+    NoRefund {},
+
+    #[error("InvalidDutchAuction")]
+    InvalidDutchAuction {},
+
+    #[error("NativePaymentDisabled")]
+    NativePaymentDisabled {},
+
+    #[error("InvalidQuantity")]
+    InvalidQuantity {},
+
     #[error("SoldOut")]
     SoldOut {},
 
+    #[error("Paused")]
+    Paused {},
+
     #[error("UnauthorizedTokenContract")]
     UnauthorizedTokenContract {},
 
@@ -35,4 +88,46 @@ pub enum ContractError {
 
     #[error("Cw721AlreadyLinked")]
     Cw721AlreadyLinked {},
+
+    #[error("NotMinter")]
+    NotMinter {},
+
+    #[error("InvalidMerkleRoot")]
+    InvalidMerkleRoot {},
+
+    #[error("InvalidMerkleProof")]
+    InvalidMerkleProof {},
+
+    #[error("AllowanceExceeded")]
+    AllowanceExceeded {},
+
+    #[error("WalletLimitReached")]
+    WalletLimitReached {},
+
+    #[error("InvalidSalePhase: {name}")]
+    InvalidSalePhase { name: String },
+
+    #[error("NoActivePhase")]
+    NoActivePhase {},
+
+    #[error("InvalidPayees")]
+    InvalidPayees {},
+
+    #[error("InsufficientProceeds")]
+    InsufficientProceeds {},
+
+    #[error("MetadataLocked")]
+    MetadataLocked {},
+
+    #[error("InvalidTokenId: {token_id}")]
+    InvalidTokenId { token_id: u32 },
+
+    #[error("AuctionNotStarted")]
+    AuctionNotStarted {},
+
+    #[error("AuctionNotSettled")]
+    AuctionNotSettled {},
+
+    #[error("NoRebate")]
+    NoRebate {},
 }
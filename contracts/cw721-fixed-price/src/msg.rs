@@ -1,25 +1,146 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Uint128};
 use cw20::Cw20ReceiveMsg;
-use cw721::state::DefaultOptionMetadataExtension;
+use cw721::state::{DefaultOptionMetadataExtension, Metadata};
+use cw_ownable::Action;
+
+use crate::state::{DutchAuction, Payee, SalePhase, TokenAssignment};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: Addr,
     pub max_tokens: u32,
+    /// Maximum number of tokens a single wallet may buy, unlimited if not set
+    pub max_per_wallet: Option<u32>,
     pub unit_price: Uint128,
     pub name: String,
     pub symbol: String,
+    /// Code id of the cw721 instantiated for the sale, unused if `cw721_address` is set
     pub token_code_id: u64,
-    pub cw20_address: Addr,
+    /// Wasm admin of the instantiated cw721, allowing it to be migrated later.
+    /// Must not be set with `cw721_address`.
+    pub cw721_admin: Option<String>,
+    /// Sells tokens of an existing cw721 instead of instantiating one.
+    /// This contract must be its minter or the pending minter.
+    pub cw721_address: Option<String>,
+    /// Accepts payment via cw20 `Send` of this token, exclusive with `native_denom`
+    pub cw20_address: Option<Addr>,
+    /// Accepts payment via `Mint {}` with funds in this denom, exclusive with `cw20_address`
+    pub native_denom: Option<String>,
     pub token_uri: String,
+    /// Token uri of each token is the base uri followed by its token id, replaces `token_uri`
+    pub base_uri: Option<String>,
     pub extension: DefaultOptionMetadataExtension,
     pub withdraw_address: Option<String>,
+    /// Enables ownership snapshots of the instantiated cw721, unused if `cw721_address` is set
+    pub snapshot_ownership: Option<bool>,
+    /// Defaults to sequential assignment
+    pub assignment: Option<TokenAssignment>,
+    /// Sells at a decaying price instead of the unit price
+    pub dutch_auction: Option<DutchAuction>,
+    /// First token id, defaults to 0. Must be past the ids of the tokens of an existing `cw721_address`.
+    pub start_token_id: Option<u32>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
+    /// Mints tokens to the sender, paid with funds in the native denom. Excess funds are refunded.
+    /// `allowance` and `proof` are required while a merkle root is set, see `ReceiveMsg`.
+    Mint {
+        quantity: Option<u32>,
+        allowance: Option<u32>,
+        proof: Option<Vec<String>>,
+    },
+    /// Restricts minting to allowlisted wallets proving their leaf, `None` opens minting to anyone.
+    /// Only owner can call this.
+    SetMerkleRoot {
+        merkle_root: Option<String>,
+    },
+    /// Replaces the sale phases, an empty list opens minting without phases.
+    /// While phases are set, price, allowlist and per-wallet limit of the active phase apply
+    /// and minting is closed outside of them.
+    /// Only owner can call this.
+    SetSalePhases {
+        phases: Vec<SalePhase>,
+    },
+    /// Withdraws collected sale proceeds, all available if `amount` is not set.
+    /// Sends to `recipient` if set, otherwise splits across the payees.
+    /// Only owner can call this.
+    WithdrawProceeds {
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    },
+    /// Replaces the split of withdrawn proceeds, an empty list sends all to the owner.
+    /// Only owner can call this.
+    SetPayees {
+        payees: Vec<Payee>,
+    },
+    /// Uploads a chunk of per token metadata, replacing the `extension` of the given token ids.
+    /// Only owner can call this, before the first token is minted.
+    UploadMetadata {
+        entries: Vec<TokenMetadata>,
+    },
+    /// Reveals the secret of the random assignment commitment, ending the sale.
+    /// Only owner can call this.
+    RevealSecret {
+        secret: String,
+    },
+    /// Mints up to `limit` reserved tokens to their buyers in order of purchase,
+    /// with ids drawn from the seed of the revealed secret.
+    DistributeTokens {
+        limit: Option<u32>,
+    },
+    /// Refunds what the sender paid for reserved tokens once the reveal deadline of the random
+    /// assignment passed without the secret being revealed.
+    ClaimRefund {},
+    /// Refunds what the sender paid above the clearing price of a rebate auction.
+    /// The auction is settled once sold out or once the price reached the floor.
+    ClaimRebate {},
+    /// Updates the given fields, `max_tokens` can only be decreased down to the number of minted tokens.
+    /// Only owner can call this.
+    UpdateConfig {
+        unit_price: Option<Uint128>,
+        max_tokens: Option<u32>,
+        token_uri: Option<String>,
+    },
+    /// Stops minting until resumed. Only owner can call this.
+    Pause {},
+    /// Only owner can call this.
+    Resume {},
+    /// Proposes a new owner, who must accept the ownership. Only owner can call this.
+    TransferOwnership {
+        new_owner: String,
+    },
+    /// Only the proposed owner can call this.
+    AcceptOwnership {},
+    /// Updates the minter ownership of the cw721, e.g. to hand it back to the creator of an
+    /// existing collection once the sale is over. Only owner can call this.
+    UpdateCollectionOwnership {
+        action: Action,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub struct TokenMetadata {
+    pub token_id: u32,
+    pub metadata: Metadata,
+}
+
+/// Payload of `Cw20ReceiveMsg.msg`, may be empty while no merkle root is set
+#[cw_serde]
+#[derive(Default)]
+pub struct ReceiveMsg {
+    /// Number of tokens to buy, defaults to 1. Payment exceeding the price is refunded.
+    pub quantity: Option<u32>,
+    /// Number of tokens the sender may mint, as encoded in its leaf `sha256("{address}:{allowance}")`
+    pub allowance: Option<u32>,
+    /// Hex encoded sibling hashes from the leaf up to the merkle root, pairs are hashed sorted
+    #[serde(default)]
+    pub proof: Vec<String>,
 }
 
 #[cw_serde]
@@ -27,18 +148,139 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     GetConfig {},
+    #[returns(MerkleRootResponse)]
+    MerkleRoot {},
+    #[returns(MintedByResponse)]
+    MintedBy { address: String },
+    #[returns(SalePhasesResponse)]
+    SalePhases {},
+    #[returns(CurrentPhaseResponse)]
+    CurrentPhase {},
+    #[returns(ProceedsResponse)]
+    Proceeds {},
+    #[returns(PayeesResponse)]
+    Payees {},
+    /// With pagination, returns the uploaded per token metadata
+    #[returns(TokenMetadataResponse)]
+    TokenMetadata {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    /// Returns the price of a single token at the current block time
+    #[returns(CurrentPriceResponse)]
+    CurrentPrice {},
+    #[returns(RebateResponse)]
+    Rebate { address: String },
+    #[returns(SaleStatusResponse)]
+    SaleStatus {},
+    /// Returns the progress of the random assignment
+    #[returns(DistributionResponse)]
+    Distribution {},
+    #[returns(OwnershipResponse)]
+    Ownership {},
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
     pub owner: Addr,
-    pub cw20_address: Addr,
+    pub cw20_address: Option<Addr>,
+    pub native_denom: Option<String>,
     pub cw721_address: Option<Addr>,
     pub max_tokens: u32,
+    pub max_per_wallet: Option<u32>,
     pub unit_price: Uint128,
     pub name: String,
     pub symbol: String,
     pub token_uri: String,
+    pub base_uri: Option<String>,
     pub extension: DefaultOptionMetadataExtension,
     pub unused_token_id: u32,
+    pub start_token_id: u32,
+    pub assignment: TokenAssignment,
+    pub dutch_auction: Option<DutchAuction>,
+    pub paused: bool,
+}
+
+#[cw_serde]
+pub struct MerkleRootResponse {
+    /// Hex encoded root of the allowlist, minting is open to anyone if not set
+    pub merkle_root: Option<String>,
+}
+
+#[cw_serde]
+pub struct MintedByResponse {
+    /// Number of tokens bought by the address
+    pub count: u32,
+}
+
+#[cw_serde]
+pub struct SalePhasesResponse {
+    pub phases: Vec<SalePhase>,
+}
+
+#[cw_serde]
+pub struct CurrentPhaseResponse {
+    /// Active phase, `None` if no phase is active
+    pub phase: Option<SalePhase>,
+}
+
+#[cw_serde]
+pub struct ProceedsResponse {
+    /// Total amount paid for minted tokens, in the configured cw20 token or native denom
+    pub collected: Uint128,
+    pub withdrawn: Uint128,
+}
+
+#[cw_serde]
+pub struct PayeesResponse {
+    pub payees: Vec<Payee>,
+}
+
+#[cw_serde]
+pub struct TokenMetadataResponse {
+    pub entries: Vec<TokenMetadata>,
+}
+
+#[cw_serde]
+pub struct CurrentPriceResponse {
+    pub price: Uint128,
+}
+
+#[cw_serde]
+pub struct RebateResponse {
+    /// Unclaimed amount paid in the auction
+    pub paid: Uint128,
+    pub quantity: u32,
+    /// Refundable amount, zero until the auction is settled
+    pub claimable: Uint128,
+}
+
+#[cw_serde]
+pub struct SaleStatusResponse {
+    /// Whether tokens can be minted at the current block
+    pub open: bool,
+    pub paused: bool,
+    pub minted: u32,
+    pub max_tokens: u32,
+    /// Name of the active sale phase
+    pub phase: Option<String>,
+    /// Price of a single token at the current block time
+    pub price: Uint128,
+}
+
+#[cw_serde]
+pub struct DistributionResponse {
+    /// Whether the secret of the commitment is revealed
+    pub revealed: bool,
+    /// Number of sold tokens
+    pub reserved: u32,
+    /// Number of sold tokens minted to their buyers
+    pub distributed: u32,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    pub owner: Addr,
+    /// Proposed new owner, until it accepts the ownership
+    pub pending_owner: Option<Addr>,
 }
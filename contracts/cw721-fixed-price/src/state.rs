@@ -1,23 +1,156 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_utils::Expiration;
 
 // expose to all others using contract, so others dont need to import cw721
 pub use cw721::state::*;
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
-    pub cw20_address: Addr,
+    pub cw20_address: Option<Addr>,
+    pub native_denom: Option<String>,
     pub cw721_address: Option<Addr>,
     pub max_tokens: u32,
+    pub max_per_wallet: Option<u32>,
     pub unit_price: Uint128,
     pub name: String,
     pub symbol: String,
     pub token_uri: String,
+    /// Token uri of each token is the base uri followed by its token id, replaces `token_uri`
+    pub base_uri: Option<String>,
     pub extension: DefaultOptionMetadataExtension,
+    /// Number of minted tokens, also the next token id for sequential assignment
     pub unused_token_id: u32,
+    /// Offset of all minted token ids, e.g. past the ids of an existing collection
+    #[serde(default)]
+    pub start_token_id: u32,
+    #[serde(default)]
+    pub assignment: TokenAssignment,
+    /// Replaces the unit price of the config and of sale phases
+    pub dutch_auction: Option<DutchAuction>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Price decaying from `start_price` by `price_step` every `interval` seconds, down to `floor_price`
+#[cw_serde]
+pub struct DutchAuction {
+    pub start_time: Timestamp,
+    pub start_price: Uint128,
+    pub floor_price: Uint128,
+    pub price_step: Uint128,
+    pub interval: u64,
+    /// Buyers can claim back what they paid above the clearing price once the auction is settled
+    pub rebate: bool,
+}
+
+impl DutchAuction {
+    pub fn price_at(&self, time: Timestamp) -> Uint128 {
+        let elapsed = time.seconds().saturating_sub(self.start_time.seconds());
+        let decay = self
+            .price_step
+            .saturating_mul(Uint128::from(elapsed / self.interval));
+        self.start_price.saturating_sub(decay).max(self.floor_price)
+    }
+}
+
+/// Payments of a buyer in a rebate auction
+#[cw_serde]
+#[derive(Default)]
+pub struct RebateDeposit {
+    pub paid: Uint128,
+    pub quantity: u32,
+}
+
+/// How token ids are assigned to buyers
+#[cw_serde]
+#[derive(Default)]
+pub enum TokenAssignment {
+    /// Token ids are minted in order
+    #[default]
+    Sequential,
+    /// Sold tokens are reserved in order of purchase and their ids are drawn at random once the
+    /// owner reveals the secret of the hex encoded sha256 `commitment`, mixed with the reveal block.
+    /// Buyers are refunded if the secret is not revealed before `reveal_deadline`.
+    Random {
+        commitment: String,
+        reveal_deadline: Expiration,
+    },
+}
+
+/// A sold token awaiting the random assignment of its id
+#[cw_serde]
+pub struct Reservation {
+    pub buyer: Addr,
+    /// Block of the purchase, mixed into the draw of the token id
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+/// A time window of the sale with its own price and restrictions
+#[cw_serde]
+pub struct SalePhase {
+    /// Unique name of the phase, e.g. "presale"
+    pub name: String,
+    pub start: Expiration,
+    pub end: Expiration,
+    pub unit_price: Uint128,
+    /// Hex encoded root of the phase allowlist, the phase is open to anyone if not set
+    pub merkle_root: Option<String>,
+    /// Maximum number of tokens a single wallet may buy during the phase
+    pub max_per_wallet: Option<u32>,
+}
+
+/// Receiver of a share of the withdrawn proceeds
+#[cw_serde]
+pub struct Payee {
+    pub address: Addr,
+    /// Share in basis points, shares of all payees add up to 10000
+    pub bps: u16,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Proceeds {
+    /// Total amount paid for minted tokens, excluding refunds
+    pub collected: Uint128,
+    pub withdrawn: Uint128,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Proposed new owner, until it accepts the ownership
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+/// Hex encoded root of the allowlist
+pub const MERKLE_ROOT: Item<String> = Item::new("merkle_root");
+/// Number of tokens minted by each allowlisted wallet
+pub const ALLOWLIST_MINTS: Map<&Addr, u32> = Map::new("allowlist_mints");
+/// Number of tokens minted by each buyer
+pub const MINTED_BY: Map<&Addr, u32> = Map::new("minted_by");
+/// Sale phases, the first active one applies. Sale is open without restrictions of a phase if not set.
+pub const SALE_PHASES: Item<Vec<SalePhase>> = Item::new("sale_phases");
+/// Number of tokens minted by each wallet per phase name
+pub const PHASE_MINTS: Map<(&str, &Addr), u32> = Map::new("phase_mints");
+/// Split of withdrawn proceeds, all goes to the owner if not set
+pub const PAYEES: Item<Vec<Payee>> = Item::new("payees");
+pub const PROCEEDS: Item<Proceeds> = Item::new("proceeds");
+/// Swap-and-pop pool of remaining token ids for random assignment, positions not set hold their own index
+pub const REMAINING_IDS: Map<u32, u32> = Map::new("remaining_ids");
+/// Buyer of each sold token by order of purchase for random assignment, until distributed
+pub const RESERVATIONS: Map<u32, Reservation> = Map::new("reservations");
+/// Amount paid by each buyer for reserved tokens, refunded if the secret is not revealed in time
+pub const REFUNDABLE: Map<&Addr, Uint128> = Map::new("refundable");
+/// Seed of the random assignment, derived from the revealed secret and the reveal block
+pub const RANDOM_SEED: Item<Vec<u8>> = Item::new("random_seed");
+/// Number of reserved tokens minted to their buyers since the reveal
+pub const DISTRIBUTED: Item<u32> = Item::new("distributed");
+/// Metadata of each token id uploaded by the owner, replaces `extension`
+pub const TOKEN_METADATA: Map<u32, Metadata> = Map::new("token_metadata");
+/// Unclaimed rebate deposits of each buyer
+pub const REBATE_DEPOSITS: Map<&Addr, RebateDeposit> = Map::new("rebate_deposits");
+/// Sum of all unclaimed rebate deposits
+pub const REBATE_TOTAL: Item<RebateDeposit> = Item::new("rebate_total");
+/// Price of the latest auction sale, the clearing price once sold out
+pub const LAST_AUCTION_PRICE: Item<Uint128> = Item::new("last_auction_price");
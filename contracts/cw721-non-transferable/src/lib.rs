@@ -1,5 +1,7 @@
-pub use crate::msg::{InstantiateMsg, QueryMsg};
-use cosmwasm_std::Empty;
+pub use crate::msg::{InstantiateMsg, QueryExtensionMsg, QueryMsg};
+use cosmwasm_std::{to_json_binary, Binary, Deps, Empty, Env, StdResult};
+use cw721::execute::Cw721Execute;
+use cw721::query::Cw721Query;
 use cw721::state::DefaultOptionMetadataExtension;
 pub use cw721_base::{
     entry::{execute as _execute, query as _query},
@@ -14,21 +16,42 @@ pub mod state;
 const CONTRACT_NAME: &str = "crates.io:cw721-non-transferable";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub type Cw721NonTransferableContract<'a> =
-    Cw721Contract<'a, DefaultOptionMetadataExtension, Empty, Empty>;
+pub struct Cw721NonTransferableContract<'a>(
+    pub Cw721Contract<'a, DefaultOptionMetadataExtension, Empty, Empty, QueryExtensionMsg>,
+);
+
+impl Default for Cw721NonTransferableContract<'static> {
+    fn default() -> Self {
+        Self(Cw721Contract::default())
+    }
+}
+
+impl<'a> Cw721Execute<DefaultOptionMetadataExtension, Empty, Empty>
+    for Cw721NonTransferableContract<'a>
+{
+}
+
+impl<'a> Cw721Query<DefaultOptionMetadataExtension, QueryExtensionMsg>
+    for Cw721NonTransferableContract<'a>
+{
+    fn query_extension(&self, deps: Deps, _env: Env, msg: QueryExtensionMsg) -> StdResult<Binary> {
+        match msg {
+            QueryExtensionMsg::Admin {} => to_json_binary(&query::admin(deps)?),
+        }
+    }
+}
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
     use super::*;
-    use crate::query::admin;
     use crate::state::{Config, CONFIG};
     use cosmwasm_std::{
-        entry_point, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-        StdResult,
+        entry_point, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
     };
     use cw721::error::Cw721ContractError;
     use cw721::execute::Cw721Execute;
     use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg};
+    use cw721::query::Cw721Query;
 
     #[entry_point]
     pub fn instantiate(
@@ -52,6 +75,9 @@ pub mod entry {
             symbol: msg.symbol,
             minter: msg.minter,
             withdraw_address: msg.withdraw_address,
+            snapshot_ownership: None,
+            clawback_enabled: None,
+            clear_user_on_transfer: None,
         };
 
         Cw721NonTransferableContract::default().instantiate(
@@ -95,7 +121,7 @@ pub mod entry {
                     token_uri,
                     extension,
                 } => Cw721NonTransferableContract::default()
-                    .mint(deps, info, token_id, owner, token_uri, extension),
+                    .mint(deps, env, info, token_id, owner, token_uri, extension),
                 _ => Err(Cw721ContractError::Ownership(
                     cw721_base::OwnershipError::NotOwner,
                 )),
@@ -103,11 +129,13 @@ pub mod entry {
         }
     }
 
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, Cw721ContractError> {
+        Cw721NonTransferableContract::default().reply(deps, env, msg)
+    }
+
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-        match msg {
-            QueryMsg::Admin {} => to_json_binary(&admin(deps)?),
-            _ => _query(deps, env, msg.into()),
-        }
+        Cw721NonTransferableContract::default().query(deps, env, msg.into())
     }
 }
@@ -1,4 +1,4 @@
-use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::{cw_serde, QueryResponses};
 // expose to all others using contract, so others dont need to import cw721
 pub use cw721::msg::{Cw721ExecuteMsg as ExecuteMsg, Cw721MigrateMsg as MigrateMsg, *};
 use cw721::state::DefaultOptionMetadataExtension;
@@ -12,96 +12,34 @@ pub struct InstantiateMsg {
     pub withdraw_address: Option<String>,
 }
 
+/// Accepts the non-transferable queries at top level next to all `cw721` queries.
+/// They can also be passed as `Cw721QueryMsg::Extension { msg }`.
 #[cw_serde]
+#[derive(QueryResponses)]
+#[query_responses(nested)]
+#[serde(untagged)]
 pub enum QueryMsg {
-    Admin {},
-
-    // -- below copied from Cw721QueryMsg
-    OwnerOf {
-        token_id: String,
-        include_expired: Option<bool>,
-    },
-    Approval {
-        token_id: String,
-        spender: String,
-        include_expired: Option<bool>,
-    },
-    Approvals {
-        token_id: String,
-        include_expired: Option<bool>,
-    },
-    AllOperators {
-        owner: String,
-        include_expired: Option<bool>,
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-    NumTokens {},
-    ContractInfo {},
-
-    NftInfo {
-        token_id: String,
-    },
-    AllNftInfo {
-        token_id: String,
-        include_expired: Option<bool>,
-    },
-    Tokens {
-        owner: String,
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-    AllTokens {
-        start_after: Option<String>,
-        limit: Option<u32>,
-    },
-    Minter {},
-
-    GetWithdrawAddress {},
+    NonTransferable(QueryExtensionMsg),
+    Base(Cw721QueryMsg<DefaultOptionMetadataExtension, QueryExtensionMsg>),
 }
 
-impl From<QueryMsg> for Cw721QueryMsg<DefaultOptionMetadataExtension> {
-    fn from(msg: QueryMsg) -> Cw721QueryMsg<DefaultOptionMetadataExtension> {
+impl From<QueryMsg> for Cw721QueryMsg<DefaultOptionMetadataExtension, QueryExtensionMsg> {
+    fn from(msg: QueryMsg) -> Self {
         match msg {
-            QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            } => Cw721QueryMsg::OwnerOf {
-                token_id,
-                include_expired,
-            },
-            QueryMsg::NumTokens {} => Cw721QueryMsg::NumTokens {},
-            QueryMsg::ContractInfo {} => Cw721QueryMsg::ContractInfo {},
-            QueryMsg::NftInfo { token_id } => Cw721QueryMsg::NftInfo { token_id },
-            QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            } => Cw721QueryMsg::AllNftInfo {
-                token_id,
-                include_expired,
-            },
-            QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            } => Cw721QueryMsg::Tokens {
-                owner,
-                start_after,
-                limit,
-            },
-            QueryMsg::AllTokens { start_after, limit } => {
-                Cw721QueryMsg::AllTokens { start_after, limit }
-            }
-            QueryMsg::Minter {} => Cw721QueryMsg::Minter {},
-            QueryMsg::GetWithdrawAddress {} => Cw721QueryMsg::GetWithdrawAddress {},
-            QueryMsg::AllOperators { .. } => unreachable!("AllOperators is not supported!"),
-            QueryMsg::Approval { .. } => unreachable!("Approval is not supported!"),
-            QueryMsg::Approvals { .. } => unreachable!("Approvals is not supported!"),
-            QueryMsg::Admin { .. } => unreachable!("Approvals is not supported!"),
+            QueryMsg::NonTransferable(msg) => Cw721QueryMsg::Extension { msg },
+            QueryMsg::Base(msg) => msg,
         }
     }
 }
 
+/// Non-transferable specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryExtensionMsg {
+    #[returns(AdminResponse)]
+    Admin {},
+}
+
 #[cw_serde]
 pub struct AdminResponse {
     pub admin: Option<String>,
@@ -127,6 +127,9 @@ fn setup_contracts(app: &mut App, admin: Addr) -> Contracts {
                 symbol: "NFT".to_string(),
                 minter: Some(admin.to_string()),
                 withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
             },
             &[],
             "nft".to_string(),
@@ -0,0 +1,117 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+};
+use cw721::transfer_policy::CanTransferResponse;
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::state::{Config, TradingWindow, BLOCKED, CONFIG};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if let Some(trading_window) = &msg.trading_window {
+        validate_trading_window(trading_window)?;
+    }
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            admin: info.sender.clone(),
+            trading_window: msg.trading_window,
+        },
+    )?;
+    for address in msg.blocked {
+        let addr = deps.api.addr_validate(&address)?;
+        BLOCKED.save(deps.storage, &addr, &Empty {})?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("admin", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    match msg {
+        ExecuteMsg::SetTradingWindow { trading_window } => {
+            if let Some(trading_window) = &trading_window {
+                validate_trading_window(trading_window)?;
+            }
+            config.trading_window = trading_window;
+            CONFIG.save(deps.storage, &config)?;
+            Ok(Response::new().add_attribute("action", "set_trading_window"))
+        }
+        ExecuteMsg::Block { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            BLOCKED.save(deps.storage, &addr, &Empty {})?;
+            Ok(Response::new()
+                .add_attribute("action", "block")
+                .add_attribute("address", address))
+        }
+        ExecuteMsg::Unblock { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            BLOCKED.remove(deps.storage, &addr);
+            Ok(Response::new()
+                .add_attribute("action", "unblock")
+                .add_attribute("address", address))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CanTransfer { recipient, .. } => {
+            to_json_binary(&query_can_transfer(deps, env, recipient)?)
+        }
+        QueryMsg::Config {} => to_json_binary(&CONFIG.load(deps.storage)?),
+    }
+}
+
+pub fn query_can_transfer(
+    deps: Deps,
+    env: Env,
+    recipient: String,
+) -> StdResult<CanTransferResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(trading_window) = config.trading_window {
+        if !trading_window.is_open(&env.block) {
+            return Ok(CanTransferResponse {
+                allowed: false,
+                reason: Some("trading window closed".to_string()),
+            });
+        }
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if BLOCKED.has(deps.storage, &recipient) {
+        return Ok(CanTransferResponse {
+            allowed: false,
+            reason: Some(format!("recipient {} blocked", recipient)),
+        });
+    }
+    Ok(CanTransferResponse {
+        allowed: true,
+        reason: None,
+    })
+}
+
+fn validate_trading_window(trading_window: &TradingWindow) -> Result<(), ContractError> {
+    if trading_window.start >= trading_window.end {
+        return Err(ContractError::InvalidTradingWindow {});
+    }
+    Ok(())
+}
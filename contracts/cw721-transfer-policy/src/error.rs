@@ -0,0 +1,14 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Caller is not the admin")]
+    Unauthorized {},
+
+    #[error("Trading window must start before it ends")]
+    InvalidTradingWindow {},
+}
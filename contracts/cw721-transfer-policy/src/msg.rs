@@ -0,0 +1,37 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cw721::transfer_policy::CanTransferResponse;
+
+use crate::state::{Config, TradingWindow};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub trading_window: Option<TradingWindow>,
+    pub blocked: Vec<String>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Sets the trading window, `None` allows transfers at any time. Only admin can call this.
+    SetTradingWindow {
+        trading_window: Option<TradingWindow>,
+    },
+    /// Denies transfers to the given address. Only admin can call this.
+    Block { address: String },
+    /// Allows transfers to a previously blocked address. Only admin can call this.
+    Unblock { address: String },
+}
+
+/// Extends `cw721::transfer_policy::TransferPolicyQueryMsg`
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_id: String,
+        owner: String,
+        sender: String,
+        recipient: String,
+    },
+    #[returns(Config)]
+    Config {},
+}
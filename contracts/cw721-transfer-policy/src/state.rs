@@ -0,0 +1,25 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Empty, Timestamp};
+use cw_storage_plus::{Item, Map};
+
+#[cw_serde]
+pub struct Config {
+    pub admin: Addr,
+    /// Transfers are only allowed within this window, if set
+    pub trading_window: Option<TradingWindow>,
+}
+
+#[cw_serde]
+pub struct TradingWindow {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl TradingWindow {
+    pub fn is_open(&self, block: &BlockInfo) -> bool {
+        self.start <= block.time && block.time < self.end
+    }
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const BLOCKED: Map<&Addr, Empty> = Map::new("blocked");
@@ -0,0 +1,315 @@
+use cosmwasm_std::{to_json_binary, Addr, Empty};
+use cw721::error::Cw721ContractError;
+use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg, OwnerOfResponse};
+use cw721::state::DefaultOptionMetadataExtension;
+use cw721::transfer_policy::{CanTransferResponse, TransferPolicyQueryMsg};
+use cw721_transfer_policy::msg::{ExecuteMsg, InstantiateMsg};
+use cw721_transfer_policy::state::TradingWindow;
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+type NftExecuteMsg = Cw721ExecuteMsg<DefaultOptionMetadataExtension, Empty>;
+
+#[test]
+fn test_transfer_allowed_by_policy() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+    let recipient = app.api().addr_make("recipient");
+
+    let Contracts {
+        nft_contract,
+        policy_contract,
+    } = setup_contracts(&mut app, admin.clone(), owner.clone(), vec![]);
+
+    // policy contract answers with `CanTransferResponse`
+    let res: CanTransferResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &policy_contract,
+            &TransferPolicyQueryMsg::CanTransfer {
+                token_id: "1".to_string(),
+                owner: owner.to_string(),
+                sender: owner.to_string(),
+                recipient: recipient.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(res.allowed);
+
+    app.execute_contract(
+        owner,
+        nft_contract.clone(),
+        &NftExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: "1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &nft_contract), recipient);
+}
+
+#[test]
+fn test_transfer_denied_by_policy() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+    let blocked = app.api().addr_make("blocked");
+    let recipient = app.api().addr_make("recipient");
+
+    let Contracts {
+        nft_contract,
+        policy_contract,
+    } = setup_contracts(
+        &mut app,
+        admin.clone(),
+        owner.clone(),
+        vec![blocked.to_string()],
+    );
+
+    // transfer to blocked recipient is denied
+    let err: Cw721ContractError = app
+        .execute_contract(
+            owner.clone(),
+            nft_contract.clone(),
+            &NftExecuteMsg::TransferNft {
+                recipient: blocked.to_string(),
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        Cw721ContractError::TransferNotAllowed {
+            reason: format!("recipient {} blocked", blocked)
+        }
+    );
+
+    // same for send
+    let err: Cw721ContractError = app
+        .execute_contract(
+            owner.clone(),
+            nft_contract.clone(),
+            &NftExecuteMsg::SendNft {
+                contract: blocked.to_string(),
+                token_id: "1".to_string(),
+                msg: to_json_binary("yes").unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        Cw721ContractError::TransferNotAllowed {
+            reason: format!("recipient {} blocked", blocked)
+        }
+    );
+    assert_eq!(query_owner(&app, &nft_contract), owner);
+
+    // outside trading window, transfers are denied
+    let now = app.block_info().time;
+    app.execute_contract(
+        admin.clone(),
+        policy_contract.clone(),
+        &ExecuteMsg::SetTradingWindow {
+            trading_window: Some(TradingWindow {
+                start: now.plus_seconds(100),
+                end: now.plus_seconds(200),
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+    let err: Cw721ContractError = app
+        .execute_contract(
+            owner.clone(),
+            nft_contract.clone(),
+            &NftExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        Cw721ContractError::TransferNotAllowed {
+            reason: "trading window closed".to_string()
+        }
+    );
+
+    // within trading window transfer is allowed
+    app.update_block(|block| block.time = now.plus_seconds(150));
+    app.execute_contract(
+        owner.clone(),
+        nft_contract.clone(),
+        &NftExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: "1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &nft_contract), recipient);
+
+    // without policy contract, blocked recipient can receive again
+    app.execute_contract(
+        admin,
+        nft_contract.clone(),
+        &NftExecuteMsg::SetTransferPolicy { address: None },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        recipient,
+        nft_contract.clone(),
+        &NftExecuteMsg::TransferNft {
+            recipient: blocked.to_string(),
+            token_id: "1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_owner(&app, &nft_contract), blocked);
+}
+
+#[test]
+fn test_only_creator_sets_transfer_policy() {
+    let mut app = App::default();
+    let admin = app.api().addr_make("admin");
+    let owner = app.api().addr_make("owner");
+
+    let Contracts {
+        nft_contract,
+        policy_contract,
+    } = setup_contracts(&mut app, admin, owner.clone(), vec![]);
+
+    let err: Cw721ContractError = app
+        .execute_contract(
+            owner,
+            nft_contract.clone(),
+            &NftExecuteMsg::SetTransferPolicy {
+                address: Some(policy_contract.to_string()),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        Cw721ContractError::Ownership(cw721_base::OwnershipError::NotOwner)
+    );
+
+    let transfer_policy: Option<String> = app
+        .wrap()
+        .query_wasm_smart(
+            &nft_contract,
+            &Cw721QueryMsg::<DefaultOptionMetadataExtension, Empty>::GetTransferPolicy {},
+        )
+        .unwrap();
+    assert_eq!(transfer_policy, Some(policy_contract.to_string()));
+}
+
+struct Contracts {
+    nft_contract: Addr,
+    policy_contract: Addr,
+}
+
+/// Setup the cw721-transfer-policy and cw721-base contracts, configure the policy and mint a test token
+fn setup_contracts(app: &mut App, admin: Addr, owner: Addr, blocked: Vec<String>) -> Contracts {
+    use cw721_transfer_policy::contract::*;
+
+    let policy_code_id =
+        app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+    let nft_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        )
+        .with_reply(cw721_base::entry::reply),
+    ));
+
+    let policy_contract = app
+        .instantiate_contract(
+            policy_code_id,
+            admin.clone(),
+            &InstantiateMsg {
+                trading_window: None,
+                blocked,
+            },
+            &[],
+            "policy".to_string(),
+            None,
+        )
+        .unwrap();
+
+    let nft_contract = app
+        .instantiate_contract(
+            nft_code_id,
+            admin.clone(),
+            &Cw721InstantiateMsg {
+                name: "nft".to_string(),
+                symbol: "NFT".to_string(),
+                minter: Some(admin.to_string()),
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            &[],
+            "nft".to_string(),
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        admin.clone(),
+        nft_contract.clone(),
+        &NftExecuteMsg::SetTransferPolicy {
+            address: Some(policy_contract.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        admin,
+        nft_contract.clone(),
+        &NftExecuteMsg::Mint {
+            token_id: "1".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    Contracts {
+        nft_contract,
+        policy_contract,
+    }
+}
+
+fn query_owner(app: &App, nft_contract: &Addr) -> Addr {
+    let res: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            nft_contract,
+            &Cw721QueryMsg::<DefaultOptionMetadataExtension, Empty>::OwnerOf {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    Addr::unchecked(res.owner)
+}
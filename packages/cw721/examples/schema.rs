@@ -5,14 +5,17 @@ use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, s
 
 use cosmwasm_std::Empty;
 use cw721::{
+    hooks::Cw721HookMsg,
     msg::{
-        AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, CollectionInfoMsg,
-        Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721MigrateMsg, Cw721QueryMsg, MinterResponse,
-        NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
-        TokensResponse,
+        AddressesResponse, AllNftInfoResponse, ApprovalResponse, ApprovalsResponse,
+        BalanceResponse, CollectionInfoMsg, CollectionStatsResponse, Cw721ExecuteMsg,
+        Cw721InstantiateMsg, Cw721MigrateMsg, Cw721QueryMsg, HooksResponse, MintVoucher,
+        MinterResponse, NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse,
+        OwnerOfAtHeightResponse, OwnerOfResponse, OwnersResponse, PermitNonceResponse,
+        PermitPayload, TokensResponse, UserOfResponse,
     },
     receiver::Cw721ReceiveMsg,
-    state::{CollectionInfo, DefaultOptionMetadataExtension},
+    state::{CollectionInfo, DefaultOptionMetadataExtension, MetadataValidation, RecipientPolicy},
 };
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -32,7 +35,7 @@ fn main() {
         "Cw721ExecuteMsg",
     );
     export_schema_with_title(
-        &schema_for!(Cw721QueryMsg<Empty>),
+        &schema_for!(Cw721QueryMsg<Empty, Empty>),
         &out_dir,
         "Cw721QueryMsg",
     );
@@ -40,7 +43,10 @@ fn main() {
 
     // messages
     export_schema_with_title(&schema_for!(Cw721ReceiveMsg), &out_dir, "Cw721ReceiveMsg");
+    export_schema_with_title(&schema_for!(Cw721HookMsg), &out_dir, "Cw721HookMsg");
     export_schema(&schema_for!(CollectionInfoMsg), &out_dir);
+    export_schema(&schema_for!(PermitPayload), &out_dir);
+    export_schema(&schema_for!(MintVoucher), &out_dir);
 
     // responses
     export_schema_with_title(
@@ -59,7 +65,17 @@ fn main() {
     export_schema(&schema_for!(OperatorsResponse), &out_dir);
     export_schema_with_title(&schema_for!(CollectionInfo), &out_dir, "CollectionInfo");
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
+    export_schema(&schema_for!(OwnerOfAtHeightResponse), &out_dir);
     export_schema(&schema_for!(MinterResponse), &out_dir);
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(OwnersResponse), &out_dir);
+    export_schema(&schema_for!(UserOfResponse), &out_dir);
+    export_schema(&schema_for!(PermitNonceResponse), &out_dir);
+    export_schema(&schema_for!(CollectionStatsResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(MetadataValidation), &out_dir);
+    export_schema(&schema_for!(RecipientPolicy), &out_dir);
+    export_schema(&schema_for!(AddressesResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
 }
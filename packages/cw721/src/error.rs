@@ -1,5 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, StdError};
 use cw_ownable::OwnershipError;
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -13,6 +14,9 @@ pub enum Cw721ContractError {
     #[error(transparent)]
     Version(#[from] cw2::VersionError),
 
+    #[error(transparent)]
+    Payment(#[from] PaymentError),
+
     #[error("token_id already claimed")]
     Claimed {},
 
@@ -24,4 +28,86 @@ pub enum Cw721ContractError {
 
     #[error("No withdraw address set")]
     NoWithdrawAddress {},
+
+    #[error("Metadata field {field} exceeds max length of {max}")]
+    MetadataFieldTooLong { field: String, max: u32 },
+
+    #[error("Too many attributes, max is {max}")]
+    TooManyAttributes { max: u32 },
+
+    #[error("Attribute trait_type must not be empty")]
+    EmptyTraitType {},
+
+    #[error("Invalid url in {field}: {url}")]
+    InvalidUrl { field: String, url: String },
+
+    #[error("Url scheme {scheme} not allowed in {field}")]
+    UrlSchemeNotAllowed { field: String, scheme: String },
+
+    #[error("Invalid background color: {color}")]
+    InvalidBackgroundColor { color: String },
+
+    #[error("Invalid metadata validation: {reason}")]
+    InvalidMetadataValidation { reason: String },
+
+    /// Raised by `Cw721Execute::validate_extension` overrides of contracts with custom metadata
+    #[error("Invalid extension: {reason}")]
+    InvalidExtension { reason: String },
+
+    #[error("Recipient not allowed: {recipient}")]
+    RecipientNotAllowed { recipient: String },
+
+    #[error("Hook already registered: {address}")]
+    HookAlreadyRegistered { address: String },
+
+    #[error("Too many hooks, max: {max}")]
+    TooManyHooks { max: u32 },
+
+    #[error("Hook not registered: {address}")]
+    HookNotRegistered { address: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Transfer not allowed by policy: {reason}")]
+    TransferNotAllowed { reason: String },
+
+    #[error("No collection stats migration pending")]
+    NoCollectionStatsMigration {},
+
+    #[error("Clawback is not enabled for this collection")]
+    ClawbackDisabled {},
+
+    #[error("Permit is not valid for this chain and contract")]
+    PermitMismatch {},
+
+    #[error("Invalid permit signature")]
+    InvalidPermitSignature {},
+
+    #[error("Invalid permit nonce, expected {expected}")]
+    InvalidPermitNonce { expected: u64 },
+
+    #[error("Voucher signer must be a compressed secp256k1 public key")]
+    InvalidVoucherSigner {},
+
+    #[error("No voucher signer set")]
+    NoVoucherSigner {},
+
+    #[error("Voucher is not valid for this chain and contract")]
+    VoucherMismatch {},
+
+    #[error("Invalid voucher signature")]
+    InvalidVoucherSignature {},
+
+    #[error("Voucher expired")]
+    VoucherExpired {},
+
+    #[error("Extension does not match the voucher")]
+    VoucherExtensionMismatch {},
+
+    #[error("Voucher with nonce {nonce} already redeemed")]
+    VoucherRedeemed { nonce: u64 },
+
+    #[error("Invalid payment, expected {expected}")]
+    InvalidVoucherPayment { expected: Coin },
 }
@@ -1,21 +1,34 @@
 use cosmwasm_std::{
-    Addr, Api, BankMsg, Binary, Coin, CustomMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response,
-    StdResult, Storage,
+    from_json, to_json_vec, Addr, Api, BankMsg, Binary, CanonicalAddr, Coin, CustomMsg, Deps,
+    DepsMut, Empty, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult,
 };
 use cw_ownable::{none_or, Action, Ownership, OwnershipError, OwnershipStore};
-use cw_storage_plus::Item;
-use cw_utils::Expiration;
+use cw_storage_plus::{Bound, Item, Map};
+use cw_utils::{must_pay, nonpayable, Expiration};
+use ripemd::Ripemd160;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::any::type_name;
 
 use crate::{
     error::Cw721ContractError,
-    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721MigrateMsg},
+    hooks::{Cw721HookAction, Cw721HookMsg, HOOK_REPLY_ID, MAX_HOOKS},
+    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721MigrateMsg, MintVoucher, PermitPayload},
+    query::MAX_LIMIT,
     receiver::Cw721ReceiveMsg,
-    state::{CollectionInfo, Cw721Config, DefaultOptionMetadataExtension, NftInfo, MINTER},
+    state::{
+        CollectionInfo, Cw721Config, DefaultOptionMetadataExtension, HookErrorPolicy,
+        MetadataValidation, NftInfo, RecipientPolicy, TokenUser, MINTER,
+    },
+    transfer_policy::{CanTransferResponse, TransferPolicyQueryMsg},
     Approval,
 };
 
+/// Tokens counted per page by the collection stats migration, if no limit is given.
+pub const DEFAULT_STATS_MIGRATION_LIMIT: u32 = 100;
+
 pub trait Cw721Execute<
     // Metadata defined in NftInfo (used for mint).
     TMetadataExtension,
@@ -53,6 +66,16 @@ pub trait Cw721Execute<
         };
         self.initialize_minter(deps.storage, deps.api, Some(minter.as_ref()))?;
 
+        if msg.snapshot_ownership.unwrap_or(false) {
+            config.snapshot_ownership.save(deps.storage, &true)?;
+        }
+        if msg.clawback_enabled.unwrap_or(false) {
+            config.clawback_enabled.save(deps.storage, &true)?;
+        }
+        if !msg.clear_user_on_transfer.unwrap_or(true) {
+            config.clear_user_on_transfer.save(deps.storage, &false)?;
+        }
+
         if let Some(withdraw_address) = msg.withdraw_address {
             self.set_withdraw_address(deps, &minter, withdraw_address)?;
         }
@@ -73,7 +96,7 @@ pub trait Cw721Execute<
                 owner,
                 token_uri,
                 extension,
-            } => self.mint(deps, info, token_id, owner, token_uri, extension),
+            } => self.mint(deps, env, info, token_id, owner, token_uri, extension),
             Cw721ExecuteMsg::Approve {
                 spender,
                 token_id,
@@ -102,6 +125,11 @@ pub trait Cw721Execute<
             Cw721ExecuteMsg::Extension { msg } => {
                 self.update_metadata_extension(deps, env, info, msg)
             }
+            Cw721ExecuteMsg::UpdateNftInfo {
+                token_id,
+                token_uri,
+                extension,
+            } => self.update_nft_info(deps, env, info, token_id, token_uri, extension),
             Cw721ExecuteMsg::SetWithdrawAddress { address } => {
                 self.set_withdraw_address(deps, &info.sender, address)
             }
@@ -109,6 +137,81 @@ pub trait Cw721Execute<
                 self.remove_withdraw_address(deps.storage, &info.sender)
             }
             Cw721ExecuteMsg::WithdrawFunds { amount } => self.withdraw_funds(deps.storage, &amount),
+            Cw721ExecuteMsg::UpdateMetadataValidation { validation } => {
+                self.update_metadata_validation(deps.storage, &info.sender, validation)
+            }
+            Cw721ExecuteMsg::SetRecipientPolicy { policy } => {
+                self.set_recipient_policy(deps.storage, &info.sender, policy)
+            }
+            Cw721ExecuteMsg::UpdateAllowlist { add, remove } => {
+                self.update_allowlist(deps, &info.sender, add, remove)
+            }
+            Cw721ExecuteMsg::UpdateBlocklist { add, remove } => {
+                self.update_blocklist(deps, &info.sender, add, remove)
+            }
+            Cw721ExecuteMsg::AddHook {
+                address,
+                error_policy,
+            } => self.add_hook(deps, &info.sender, address, error_policy),
+            Cw721ExecuteMsg::RemoveHook { address } => {
+                self.remove_hook(deps, &info.sender, address)
+            }
+            Cw721ExecuteMsg::SetTransferPolicy { address } => {
+                self.set_transfer_policy(deps, &info.sender, address)
+            }
+            Cw721ExecuteMsg::MigrateCollectionStats { limit } => {
+                self.continue_collection_stats_migration(deps.storage, limit)
+            }
+            Cw721ExecuteMsg::SetUser {
+                token_id,
+                user,
+                expires,
+            } => self.set_user(deps, env, info, token_id, user, expires),
+            Cw721ExecuteMsg::ForceTransfer {
+                token_id,
+                recipient,
+                reason,
+            } => self.force_transfer(deps, env, info, token_id, recipient, reason),
+            Cw721ExecuteMsg::ForceBurn { token_id, reason } => {
+                self.force_burn(deps, env, info, token_id, reason)
+            }
+            Cw721ExecuteMsg::Permit {
+                payload,
+                pub_key,
+                signature,
+            } => self.permit(deps, env, info, payload, pub_key, signature),
+            Cw721ExecuteMsg::RevokePermit { nonce } => {
+                self.revoke_permit(deps.storage, &info.sender, nonce)
+            }
+            Cw721ExecuteMsg::SetVoucherSigner { pub_key } => {
+                self.set_voucher_signer(deps, &info.sender, pub_key)
+            }
+            Cw721ExecuteMsg::RedeemVoucher {
+                voucher,
+                extension,
+                signature,
+            } => self.redeem_voucher(deps, env, info, voucher, extension, signature),
+        }
+    }
+
+    /// Handles replies of failing hooks registered with `HookErrorPolicy::Ignore`.
+    fn reply(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        msg: Reply,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        match msg.id {
+            HOOK_REPLY_ID => {
+                let error = match msg.result {
+                    SubMsgResult::Err(error) => error,
+                    SubMsgResult::Ok(_) => String::new(),
+                };
+                Ok(Response::new()
+                    .add_attribute("action", "hook_failed")
+                    .add_attribute("error", error))
+            }
+            id => Err(Cw721ContractError::UnknownReplyId { id }),
         }
     }
 
@@ -129,6 +232,8 @@ pub trait Cw721Execute<
         let response = migrate_version(deps.storage, contract_name, contract_version, response)?;
         // ... and update creator and minter AFTER legacy migration
         let response = migrate_minter(deps.storage, deps.api, &env, &msg, response)?;
+        let response =
+            migrate_collection_stats::<TMetadataExtension>(deps.storage, &env, &msg, response)?;
         Ok(response)
     }
 
@@ -141,9 +246,16 @@ pub trait Cw721Execute<
         recipient: String,
         token_id: String,
     ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let hooks = transfer_hook_submsgs::<TMetadataExtension, TCustomResponseMessage>(
+            deps.storage,
+            &recipient,
+            &token_id,
+            Cw721HookAction::Transfer,
+        )?;
         _transfer_nft::<TMetadataExtension>(deps, &env, &info, &recipient, &token_id)?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "transfer_nft")
             .add_attribute("sender", info.sender)
             .add_attribute("recipient", recipient)
@@ -159,6 +271,12 @@ pub trait Cw721Execute<
         token_id: String,
         msg: Binary,
     ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let hooks = transfer_hook_submsgs::<TMetadataExtension, TCustomResponseMessage>(
+            deps.storage,
+            &contract,
+            &token_id,
+            Cw721HookAction::Send,
+        )?;
         // Transfer token
         _transfer_nft::<TMetadataExtension>(deps, &env, &info, &contract, &token_id)?;
 
@@ -171,6 +289,7 @@ pub trait Cw721Execute<
         // Send message
         Ok(Response::new()
             .add_message(send.into_cosmos_msg(contract.clone())?)
+            .add_submessages(hooks)
             .add_attribute("action", "send_nft")
             .add_attribute("sender", info.sender)
             .add_attribute("recipient", contract)
@@ -288,9 +407,22 @@ pub trait Cw721Execute<
         check_can_send(deps.as_ref(), &env, &info, &token)?;
 
         config.nft_info.remove(deps.storage, &token_id)?;
+        config.token_users.remove(deps.storage, &token_id)?;
         config.decrement_tokens(deps.storage)?;
+        config.record_burn(deps.storage)?;
+        config.update_balances(deps.storage, &token_id, Some(&token.owner), None)?;
+        config.snapshot_owner(deps.storage, &token_id, None, env.block.height)?;
+
+        let hooks = Cw721HookMsg {
+            token_id: token_id.clone(),
+            from: Some(token.owner.to_string()),
+            to: None,
+            action: Cw721HookAction::Burn,
+        }
+        .into_submsgs(deps.storage)?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "burn")
             .add_attribute("sender", info.sender)
             .add_attribute("token_id", token_id))
@@ -306,9 +438,11 @@ pub trait Cw721Execute<
         MINTER.initialize_owner(storage, api, minter)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn mint(
         &self,
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         token_id: String,
         owner: String,
@@ -316,15 +450,19 @@ pub trait Cw721Execute<
         extension: TMetadataExtension,
     ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
         MINTER.assert_owner(deps.storage, &info.sender)?;
+        self.validate_extension(deps.storage, &extension)?;
+
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        config.assert_recipient_allowed(deps.storage, &owner_addr)?;
 
         // create the token
         let token = NftInfo {
-            owner: deps.api.addr_validate(&owner)?,
+            owner: owner_addr.clone(),
             approvals: vec![],
             token_uri,
             extension,
         };
-        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
         config
             .nft_info
             .update(deps.storage, &token_id, |old| match old {
@@ -333,14 +471,65 @@ pub trait Cw721Execute<
             })?;
 
         config.increment_tokens(deps.storage)?;
+        config.record_mint(deps.storage, env.block.time)?;
+        config.update_balances(deps.storage, &token_id, None, Some(&owner_addr))?;
+        config.snapshot_owner(deps.storage, &token_id, Some(&owner_addr), env.block.height)?;
+
+        let hooks = Cw721HookMsg {
+            token_id: token_id.clone(),
+            from: None,
+            to: Some(owner.clone()),
+            action: Cw721HookAction::Mint,
+        }
+        .into_submsgs(deps.storage)?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "mint")
             .add_attribute("minter", info.sender)
             .add_attribute("owner", owner)
             .add_attribute("token_id", token_id))
     }
 
+    /// Validates the metadata extension of a token on mint, voucher redemption and `UpdateNftInfo`.
+    /// Only `DefaultOptionMetadataExtension` is checked against the stored rules, any other
+    /// extension type is accepted. Contracts with custom metadata can override this.
+    fn validate_extension(
+        &self,
+        storage: &dyn Storage,
+        extension: &TMetadataExtension,
+    ) -> Result<(), Cw721ContractError> {
+        if type_name::<TMetadataExtension>() != type_name::<DefaultOptionMetadataExtension>() {
+            return Ok(());
+        }
+        let metadata: DefaultOptionMetadataExtension = from_json(to_json_vec(extension)?)?;
+        validate_metadata_extension(storage, &metadata)
+    }
+
+    fn update_nft_info(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: Option<String>,
+        extension: TMetadataExtension,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        MINTER.assert_owner(deps.storage, &info.sender)?;
+        self.validate_extension(deps.storage, &extension)?;
+
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        let mut token = config.nft_info.load(deps.storage, &token_id)?;
+        token.token_uri = token_uri;
+        token.extension = extension;
+        config.nft_info.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_nft_info")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
     fn update_minter_ownership(
         &self,
         deps: DepsMut,
@@ -355,7 +544,8 @@ pub trait Cw721Execute<
             .add_attributes(ownership.into_attributes()))
     }
 
-    /// Allows creator to update onchain metadata. For now this is a no-op.
+    /// Handles the `Extension` message of contracts with custom metadata updates, a no-op by default.
+    /// Token uri and extension are replaced with `UpdateNftInfo`.
     fn update_metadata_extension(
         &self,
         deps: DepsMut,
@@ -436,6 +626,526 @@ pub trait Cw721Execute<
             None => Err(Cw721ContractError::NoWithdrawAddress {}),
         }
     }
+
+    fn update_metadata_validation(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        validation: MetadataValidation,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(storage, sender)?;
+        validation.validate()?;
+        Cw721Config::<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>::default()
+            .metadata_validation
+            .save(storage, &validation)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_metadata_validation")
+            .add_attribute("max_field_length", validation.max_field_length.to_string())
+            .add_attribute(
+                "max_image_data_length",
+                validation.max_image_data_length.to_string(),
+            )
+            .add_attribute("max_attributes", validation.max_attributes.to_string())
+            .add_attribute(
+                "allowed_url_schemes",
+                validation.allowed_url_schemes.join(","),
+            ))
+    }
+
+    fn set_recipient_policy(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        policy: RecipientPolicy,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(storage, sender)?;
+        Cw721Config::<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>::default()
+            .recipient_policy
+            .save(storage, &policy)?;
+        Ok(Response::new()
+            .add_attribute("action", "set_recipient_policy")
+            .add_attribute("policy", format!("{:?}", policy)))
+    }
+
+    fn update_allowlist(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        _update_address_list(deps, &config.allowlist, &add, &remove)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_allowlist")
+            .add_attribute("added", add.join(","))
+            .add_attribute("removed", remove.join(",")))
+    }
+
+    fn update_blocklist(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        _update_address_list(deps, &config.blocklist, &add, &remove)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_blocklist")
+            .add_attribute("added", add.join(","))
+            .add_attribute("removed", remove.join(",")))
+    }
+
+    fn add_hook(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        address: String,
+        error_policy: HookErrorPolicy,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, sender)?;
+        let hook = deps.api.addr_validate(&address)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        if config.hooks.has(deps.storage, &hook) {
+            return Err(Cw721ContractError::HookAlreadyRegistered { address });
+        }
+        let count = config
+            .hooks
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .count();
+        if count >= MAX_HOOKS as usize {
+            return Err(Cw721ContractError::TooManyHooks { max: MAX_HOOKS });
+        }
+        config.hooks.save(deps.storage, &hook, &error_policy)?;
+        Ok(Response::new()
+            .add_attribute("action", "add_hook")
+            .add_attribute("hook", address)
+            .add_attribute("error_policy", format!("{:?}", error_policy)))
+    }
+
+    fn remove_hook(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        address: String,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, sender)?;
+        let hook = deps.api.addr_validate(&address)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        if !config.hooks.has(deps.storage, &hook) {
+            return Err(Cw721ContractError::HookNotRegistered { address });
+        }
+        config.hooks.remove(deps.storage, &hook);
+        Ok(Response::new()
+            .add_attribute("action", "remove_hook")
+            .add_attribute("hook", address))
+    }
+
+    fn set_transfer_policy(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        address: Option<String>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        match address {
+            Some(address) => {
+                let policy = deps.api.addr_validate(&address)?;
+                config.transfer_policy.save(deps.storage, &policy)?;
+            }
+            None => config.transfer_policy.remove(deps.storage),
+        }
+        let transfer_policy = config.transfer_policy.may_load(deps.storage)?;
+        Ok(Response::new()
+            .add_attribute("action", "set_transfer_policy")
+            .add_attribute("transfer_policy", none_or(transfer_policy.as_ref())))
+    }
+
+    fn set_user(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        user: Option<String>,
+        expires: Option<Expiration>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        let token = config.nft_info.load(deps.storage, &token_id)?;
+        // owner, approved spenders and operators can set the user
+        check_can_send(deps.as_ref(), &env, &info, &token)?;
+        match &user {
+            Some(user) => {
+                // reject expired data as invalid
+                let expires = expires.unwrap_or_default();
+                if expires.is_expired(&env.block) {
+                    return Err(Cw721ContractError::Expired {});
+                }
+                let token_user = TokenUser {
+                    user: deps.api.addr_validate(user)?,
+                    expires,
+                };
+                config
+                    .token_users
+                    .save(deps.storage, &token_id, &token_user)?;
+            }
+            None => config.token_users.remove(deps.storage, &token_id)?,
+        }
+        Ok(Response::new()
+            .add_attribute("action", "set_user")
+            .add_attribute("sender", info.sender)
+            .add_attribute("user", none_or(user.as_ref()))
+            .add_attribute("token_id", token_id))
+    }
+
+    /// Creator moves a token without the owner's consent, e.g. on court order.
+    /// Bypasses approvals, recipient and transfer policies.
+    fn force_transfer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        recipient: String,
+        reason: String,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        if !config.clawback_enabled(deps.storage)? {
+            return Err(Cw721ContractError::ClawbackDisabled {});
+        }
+        let recipient_addr = deps.api.addr_validate(&recipient)?;
+        let mut token = config.nft_info.load(deps.storage, &token_id)?;
+        let from = token.owner.clone();
+        config.update_balances(deps.storage, &token_id, Some(&from), Some(&recipient_addr))?;
+        token.owner = recipient_addr;
+        token.approvals = vec![];
+        config.nft_info.save(deps.storage, &token_id, &token)?;
+        config.clear_user_on_transfer(deps.storage, &token_id)?;
+        config.snapshot_owner(
+            deps.storage,
+            &token_id,
+            Some(&token.owner),
+            env.block.height,
+        )?;
+
+        let hooks = Cw721HookMsg {
+            token_id: token_id.clone(),
+            from: Some(from.to_string()),
+            to: Some(recipient.clone()),
+            action: Cw721HookAction::Transfer,
+        }
+        .into_submsgs(deps.storage)?;
+
+        Ok(Response::new()
+            .add_submessages(hooks)
+            .add_event(
+                Event::new("cw721_clawback")
+                    .add_attribute("action", "force_transfer")
+                    .add_attribute("token_id", &token_id)
+                    .add_attribute("from", &from)
+                    .add_attribute("to", &recipient)
+                    .add_attribute("reason", &reason),
+            )
+            .add_attribute("action", "force_transfer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("from", from)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reason", reason))
+    }
+
+    /// Creator burns a token without the owner's consent, e.g. after fraud.
+    fn force_burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        reason: String,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        if !config.clawback_enabled(deps.storage)? {
+            return Err(Cw721ContractError::ClawbackDisabled {});
+        }
+        let token = config.nft_info.load(deps.storage, &token_id)?;
+        config.nft_info.remove(deps.storage, &token_id)?;
+        config.token_users.remove(deps.storage, &token_id)?;
+        config.decrement_tokens(deps.storage)?;
+        config.record_burn(deps.storage)?;
+        config.update_balances(deps.storage, &token_id, Some(&token.owner), None)?;
+        config.snapshot_owner(deps.storage, &token_id, None, env.block.height)?;
+
+        let hooks = Cw721HookMsg {
+            token_id: token_id.clone(),
+            from: Some(token.owner.to_string()),
+            to: None,
+            action: Cw721HookAction::Burn,
+        }
+        .into_submsgs(deps.storage)?;
+
+        Ok(Response::new()
+            .add_submessages(hooks)
+            .add_event(
+                Event::new("cw721_clawback")
+                    .add_attribute("action", "force_burn")
+                    .add_attribute("token_id", &token_id)
+                    .add_attribute("from", &token.owner)
+                    .add_attribute("reason", &reason),
+            )
+            .add_attribute("action", "force_burn")
+            .add_attribute("sender", info.sender)
+            .add_attribute("from", token.owner)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reason", reason))
+    }
+
+    /// Applies an approval signed by the owner, as if the owner called `Approve` or `ApproveAll`.
+    fn permit(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        payload: PermitPayload,
+        pub_key: Binary,
+        signature: Binary,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        if payload.chain_id != env.block.chain_id
+            || payload.contract_address != env.contract.address
+        {
+            return Err(Cw721ContractError::PermitMismatch {});
+        }
+        let owner = derive_address(deps.api, &pub_key)?;
+        let sign_doc = adr036_sign_doc(owner.as_str(), &to_json_vec(&payload)?);
+        let hash = Sha256::digest(sign_doc);
+        let valid = deps
+            .api
+            .secp256k1_verify(&hash, &signature, &pub_key)
+            .map_err(StdError::from)?;
+        if !valid {
+            return Err(Cw721ContractError::InvalidPermitSignature {});
+        }
+
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        let nonce = config.permit_nonce(deps.storage, &owner)?;
+        if payload.nonce != nonce {
+            return Err(Cw721ContractError::InvalidPermitNonce { expected: nonce });
+        }
+        config
+            .permit_nonces
+            .save(deps.storage, &owner, &(nonce + 1))?;
+
+        let owner_info = MessageInfo {
+            sender: owner,
+            funds: vec![],
+        };
+        let res = match payload.token_id {
+            Some(token_id) => self.approve(
+                deps,
+                env,
+                owner_info,
+                payload.spender,
+                token_id,
+                payload.expires,
+            )?,
+            None => self.approve_all(deps, env, owner_info, payload.spender, payload.expires)?,
+        };
+        Ok(res
+            .add_attribute("permit_nonce", nonce.to_string())
+            .add_attribute("relayer", info.sender))
+    }
+
+    fn revoke_permit(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        nonce: u64,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        let expected = config.permit_nonce(storage, sender)?;
+        if nonce < expected {
+            return Err(Cw721ContractError::InvalidPermitNonce { expected });
+        }
+        config.permit_nonces.save(storage, sender, &(nonce + 1))?;
+        Ok(Response::new()
+            .add_attribute("action", "revoke_permit")
+            .add_attribute("owner", sender)
+            .add_attribute("permit_nonce", nonce.to_string()))
+    }
+
+    fn set_voucher_signer(
+        &self,
+        deps: DepsMut,
+        sender: &Addr,
+        pub_key: Option<Binary>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        MINTER.assert_owner(deps.storage, sender)?;
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        match &pub_key {
+            Some(pub_key) => {
+                // compressed SEC1 encoding, checked by prefix as the api can't parse keys
+                if pub_key.len() != 33 || !matches!(pub_key[0], 0x02 | 0x03) {
+                    return Err(Cw721ContractError::InvalidVoucherSigner {});
+                }
+                config.voucher_signer.save(deps.storage, pub_key)?
+            }
+            None => config.voucher_signer.remove(deps.storage),
+        }
+        Ok(Response::new()
+            .add_attribute("action", "set_voucher_signer")
+            .add_attribute("voucher_signer", none_or(pub_key.as_ref())))
+    }
+
+    /// Mints the token of a voucher signed by the voucher signer to the sender, on behalf of the minter.
+    fn redeem_voucher(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        voucher: MintVoucher,
+        extension: TMetadataExtension,
+        signature: Binary,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        let pub_key = config
+            .voucher_signer
+            .may_load(deps.storage)?
+            .ok_or(Cw721ContractError::NoVoucherSigner {})?;
+        if voucher.chain_id != env.block.chain_id
+            || voucher.contract_address != env.contract.address
+        {
+            return Err(Cw721ContractError::VoucherMismatch {});
+        }
+        let hash = Sha256::digest(to_json_vec(&voucher)?);
+        let valid = deps
+            .api
+            .secp256k1_verify(&hash, &signature, &pub_key)
+            .map_err(StdError::from)?;
+        if !valid {
+            return Err(Cw721ContractError::InvalidVoucherSignature {});
+        }
+        if voucher.expires.is_expired(&env.block) {
+            return Err(Cw721ContractError::VoucherExpired {});
+        }
+        if Sha256::digest(to_json_vec(&extension)?).as_slice() != voucher.extension_hash.as_slice()
+        {
+            return Err(Cw721ContractError::VoucherExtensionMismatch {});
+        }
+        if config.redeemed_vouchers.has(deps.storage, voucher.nonce) {
+            return Err(Cw721ContractError::VoucherRedeemed {
+                nonce: voucher.nonce,
+            });
+        }
+        if voucher.price.amount.is_zero() {
+            nonpayable(&info)?;
+        } else if must_pay(&info, &voucher.price.denom)? != voucher.price.amount {
+            return Err(Cw721ContractError::InvalidVoucherPayment {
+                expected: voucher.price,
+            });
+        }
+        config
+            .redeemed_vouchers
+            .save(deps.storage, voucher.nonce, &Empty {})?;
+
+        // payment stays in the contract and can be withdrawn to the withdraw address
+        let minter = MINTER
+            .get_ownership(deps.storage)?
+            .owner
+            .ok_or(Cw721ContractError::Ownership(OwnershipError::NoOwner))?;
+        let minter_info = MessageInfo {
+            sender: minter,
+            funds: vec![],
+        };
+        let res = self.mint(
+            deps,
+            env,
+            minter_info,
+            voucher.token_id,
+            info.sender.to_string(),
+            voucher.token_uri,
+            extension,
+        )?;
+        Ok(res
+            .add_attribute("voucher_nonce", voucher.nonce.to_string())
+            .add_attribute("buyer", info.sender))
+    }
+
+    /// Continues a pending collection stats migration, see `migrate_collection_stats`.
+    fn continue_collection_stats_migration(
+        &self,
+        storage: &mut dyn Storage,
+        limit: Option<u32>,
+    ) -> Result<Response<TCustomResponseMessage>, Cw721ContractError> {
+        let config = Cw721Config::<
+            TMetadataExtension,
+            TCustomResponseMessage,
+            TMetadataExtensionMsg,
+        >::default();
+        if !config.stats_migration.exists(storage) {
+            return Err(Cw721ContractError::NoCollectionStatsMigration {});
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_STATS_MIGRATION_LIMIT)
+            .min(MAX_LIMIT);
+        let (counted, done) = _count_collection_stats::<TMetadataExtension>(storage, limit)?;
+        Ok(Response::new()
+            .add_attribute("action", "migrate_collection_stats")
+            .add_attribute("counted", counted.to_string())
+            .add_attribute("done", done.to_string()))
+    }
 }
 
 // ------- helper cw721 functions -------
@@ -453,13 +1163,41 @@ where
     let mut token = config.nft_info.load(deps.storage, token_id)?;
     // ensure we have permissions
     check_can_send(deps.as_ref(), env, info, &token)?;
+    let recipient = deps.api.addr_validate(recipient)?;
+    config.assert_recipient_allowed(deps.storage, &recipient)?;
+    check_transfer_policy(deps.as_ref(), info, token_id, &token, &recipient)?;
+    config.update_balances(deps.storage, token_id, Some(&token.owner), Some(&recipient))?;
     // set owner and remove existing approvals
-    token.owner = deps.api.addr_validate(recipient)?;
+    token.owner = recipient;
     token.approvals = vec![];
     config.nft_info.save(deps.storage, token_id, &token)?;
+    config.clear_user_on_transfer(deps.storage, token_id)?;
+    config.snapshot_owner(deps.storage, token_id, Some(&token.owner), env.block.height)?;
     Ok(token)
 }
 
+/// Creates hook submessages for a transfer or send, `from` is the current owner of the token.
+fn transfer_hook_submsgs<TMetadataExtension, TCustomResponseMessage>(
+    storage: &dyn Storage,
+    recipient: &str,
+    token_id: &str,
+    action: Cw721HookAction,
+) -> Result<Vec<SubMsg<TCustomResponseMessage>>, Cw721ContractError>
+where
+    TMetadataExtension: Serialize + DeserializeOwned + Clone,
+    TCustomResponseMessage: CustomMsg,
+{
+    let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+    let token = config.nft_info.load(storage, token_id)?;
+    Ok(Cw721HookMsg {
+        token_id: token_id.to_string(),
+        from: Some(token.owner.to_string()),
+        to: Some(recipient.to_string()),
+        action,
+    }
+    .into_submsgs(storage)?)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn _update_approvals<TMetadataExtension>(
     deps: DepsMut,
@@ -502,6 +1240,77 @@ where
     Ok(token)
 }
 
+/// Counts balances and holders of up to `limit` tokens not yet counted by the collection stats migration.
+/// Returns the number of tokens counted and whether all tokens are counted.
+fn _count_collection_stats<TMetadataExtension>(
+    storage: &mut dyn Storage,
+    limit: u32,
+) -> StdResult<(usize, bool)>
+where
+    TMetadataExtension: Serialize + DeserializeOwned + Clone,
+{
+    let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+    let cursor = match config.stats_migration.may_load(storage)? {
+        Some(cursor) => cursor,
+        None => return Ok((0, true)),
+    };
+    let start = cursor.map(|s| Bound::ExclusiveRaw(s.into()));
+    // load one more token to know whether there are more pages
+    let mut tokens = config
+        .nft_info
+        .range(storage, start, None, Order::Ascending)
+        .take(limit as usize + 1)
+        .map(|item| item.map(|(token_id, token)| (token_id, token.owner)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = tokens.len() <= limit as usize;
+    tokens.truncate(limit as usize);
+    for (_, owner) in &tokens {
+        config.increment_balance(storage, owner)?;
+    }
+    match tokens.last() {
+        Some((token_id, _)) if !done => config
+            .stats_migration
+            .save(storage, &Some(token_id.clone()))?,
+        _ => config.stats_migration.remove(storage),
+    }
+    Ok((tokens.len(), done))
+}
+
+/// Adds addresses to and removes them from a recipient allowlist or blocklist.
+fn _update_address_list(
+    deps: DepsMut,
+    list: &Map<&Addr, Empty>,
+    add: &[String],
+    remove: &[String],
+) -> Result<(), Cw721ContractError> {
+    for address in add {
+        let addr = deps.api.addr_validate(address)?;
+        list.save(deps.storage, &addr, &Empty {})?;
+    }
+    for address in remove {
+        let addr = deps.api.addr_validate(address)?;
+        list.remove(deps.storage, &addr);
+    }
+    Ok(())
+}
+
+/// Validates the default metadata extension against the stored rules.
+/// Called on mint and `UpdateNftInfo` by `Cw721Execute::validate_extension`, contracts can also call this
+/// on metadata updates of their own.
+pub fn validate_metadata_extension(
+    storage: &dyn Storage,
+    extension: &DefaultOptionMetadataExtension,
+) -> Result<(), Cw721ContractError> {
+    match extension {
+        Some(metadata) => {
+            let rules = Cw721Config::<DefaultOptionMetadataExtension, Empty, Empty>::default()
+                .metadata_validation(storage)?;
+            metadata.validate(&rules)
+        }
+        None => Ok(()),
+    }
+}
+
 /// returns true if the sender can execute approve or reject on the contract
 pub fn check_can_approve<TMetadataExtension>(
     deps: Deps,
@@ -533,6 +1342,30 @@ where
     }
 }
 
+/// Sign doc of ADR-036 arbitrary messages, as signed by wallets' `signArbitrary`.
+/// Amino JSON with sorted keys and without whitespace.
+pub fn adr036_sign_doc(signer: &str, data: &[u8]) -> Vec<u8> {
+    format!(
+        r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"memo":"","msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","signer":"{}"}}}}],"sequence":"0"}}"#,
+        Binary::from(data).to_base64(),
+        signer
+    )
+    .into_bytes()
+}
+
+/// Derives the address of a compressed secp256k1 public key the same way as the Cosmos SDK,
+/// from `ripemd160(sha256(pub_key))`. Chains deriving addresses differently, e.g. from
+/// `eth_secp256k1` keys, are not supported.
+pub fn derive_address(api: &dyn Api, pub_key: &[u8]) -> StdResult<Addr> {
+    if pub_key.len() != 33 {
+        return Err(StdError::generic_err(
+            "Public key must be a compressed secp256k1 key",
+        ));
+    }
+    let hash = Ripemd160::digest(Sha256::digest(pub_key));
+    api.addr_humanize(&CanonicalAddr::from(hash.as_slice()))
+}
+
 /// returns true iff the sender can transfer ownership of the token
 pub fn check_can_send<TMetadataExtension>(
     deps: Deps,
@@ -573,6 +1406,37 @@ pub fn check_can_send<TMetadataExtension>(
     }
 }
 
+/// Asks the transfer policy contract, if set, whether the transfer is allowed
+pub fn check_transfer_policy<TMetadataExtension>(
+    deps: Deps,
+    info: &MessageInfo,
+    token_id: &str,
+    token: &NftInfo<TMetadataExtension>,
+    recipient: &Addr,
+) -> Result<(), Cw721ContractError> {
+    let config = Cw721Config::<Empty, Empty, Empty>::default();
+    let policy = match config.transfer_policy.may_load(deps.storage)? {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+    let res: CanTransferResponse = deps.querier.query_wasm_smart(
+        policy,
+        &TransferPolicyQueryMsg::CanTransfer {
+            token_id: token_id.to_string(),
+            owner: token.owner.to_string(),
+            sender: info.sender.to_string(),
+            recipient: recipient.to_string(),
+        },
+    )?;
+    if res.allowed {
+        Ok(())
+    } else {
+        Err(Cw721ContractError::TransferNotAllowed {
+            reason: res.reason.unwrap_or_else(|| "denied".to_string()),
+        })
+    }
+}
+
 // ------- migrate -------
 pub fn migrate_version(
     storage: &mut dyn Storage,
@@ -651,6 +1515,37 @@ pub fn migrate_legacy_minter_and_creator(
     Ok(response.add_attribute("creator_and_minter", none_or(creator_and_minter.as_ref())))
 }
 
+/// Initialises collection stats of contracts migrated from versions without stats.
+/// Total minted starts at the current token count, since burned tokens are unknown.
+/// Balances and holders are counted by walking existing tokens in pages,
+/// the first page here and the rest via `Cw721ExecuteMsg::MigrateCollectionStats`.
+pub fn migrate_collection_stats<TMetadataExtension>(
+    storage: &mut dyn Storage,
+    _env: &Env,
+    _msg: &Cw721MigrateMsg,
+    response: Response,
+) -> StdResult<Response>
+where
+    TMetadataExtension: Serialize + DeserializeOwned + Clone,
+{
+    let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+    // no migration in case stats are already tracked
+    if config.total_minted.exists(storage) || config.stats_migration.exists(storage) {
+        return Ok(response);
+    }
+    let total_minted = config.token_count(storage)?;
+    config.total_minted.save(storage, &total_minted)?;
+    config.balances.clear(storage);
+    config.num_holders.remove(storage);
+    config.stats_migration.save(storage, &None)?;
+    let (counted, done) =
+        _count_collection_stats::<TMetadataExtension>(storage, DEFAULT_STATS_MIGRATION_LIMIT)?;
+    Ok(response
+        .add_attribute("migrated total_minted", total_minted.to_string())
+        .add_attribute("migrated stats tokens", counted.to_string())
+        .add_attribute("migrated stats done", done.to_string()))
+}
+
 /// Migrates only in case collection_info is not present
 pub fn migrate_legacy_collection_info(
     storage: &mut dyn Storage,
@@ -1,15 +1,17 @@
 use std::marker::PhantomData;
 
 use crate::msg::{
-    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, NftInfoResponse, NumTokensResponse,
-    OperatorsResponse, OwnerOfResponse, TokensResponse,
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, BalanceResponse, NftInfoResponse,
+    NumTokensResponse, OperatorsResponse, OwnerBalance, OwnerOfResponse, OwnersResponse,
+    TokensResponse,
 };
 use crate::msg::{Cw721ExecuteMsg, Cw721QueryMsg};
 use crate::state::CollectionInfo;
 use crate::Approval;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    to_json_binary, Addr, CosmosMsg, CustomMsg, QuerierWrapper, StdResult, WasmMsg, WasmQuery,
+    to_json_binary, Addr, CosmosMsg, CustomMsg, Empty, QuerierWrapper, StdResult, WasmMsg,
+    WasmQuery,
 };
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -47,7 +49,7 @@ where
     pub fn query<T: DeserializeOwned>(
         &self,
         querier: &QuerierWrapper,
-        req: Cw721QueryMsg<TMetadataExtension>,
+        req: Cw721QueryMsg<TMetadataExtension, Empty>,
     ) -> StdResult<T> {
         let query = WasmQuery::Smart {
             contract_addr: self.addr().into(),
@@ -57,6 +59,22 @@ where
         querier.query(&query)
     }
 
+    /// Custom query of a contract extending cw721, passed as `Cw721QueryMsg::Extension`
+    pub fn query_extension<T: DeserializeOwned, TQueryExtensionMsg: Serialize>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: TQueryExtensionMsg,
+    ) -> StdResult<T> {
+        let req: Cw721QueryMsg<TMetadataExtension, TQueryExtensionMsg> =
+            Cw721QueryMsg::Extension { msg };
+        let query = WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_json_binary(&req)?,
+        }
+        .into();
+        querier.query(&query)
+    }
+
     /*** queries ***/
 
     pub fn owner_of<T: Into<String>>(
@@ -126,6 +144,29 @@ where
         Ok(res.count)
     }
 
+    pub fn balance_of<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        owner: T,
+    ) -> StdResult<u64> {
+        let req = Cw721QueryMsg::BalanceOf {
+            owner: owner.into(),
+        };
+        let res: BalanceResponse = self.query(querier, req)?;
+        Ok(res.balance)
+    }
+
+    pub fn all_owners(
+        &self,
+        querier: &QuerierWrapper,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<OwnerBalance>> {
+        let req = Cw721QueryMsg::AllOwners { start_after, limit };
+        let res: OwnersResponse = self.query(querier, req)?;
+        Ok(res.owners)
+    }
+
     /// With metadata extension
     pub fn collection_info(&self, querier: &QuerierWrapper) -> StdResult<CollectionInfo> {
         let req = Cw721QueryMsg::ContractInfo {};
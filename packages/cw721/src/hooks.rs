@@ -0,0 +1,90 @@
+use schemars::JsonSchema;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Binary, CosmosMsg, Empty, Order, StdResult, Storage, SubMsg, WasmMsg,
+};
+
+use crate::state::{Cw721Config, HookErrorPolicy};
+
+/// Reply id of hooks registered with `HookErrorPolicy::Ignore`.
+pub const HOOK_REPLY_ID: u64 = 7210;
+
+/// Max number of hooks, each mint, transfer, send and burn dispatches one submessage per hook.
+pub const MAX_HOOKS: u32 = 10;
+
+/// Action on a token, which triggered a hook
+#[cw_serde]
+pub enum Cw721HookAction {
+    Mint,
+    Transfer,
+    Send,
+    Burn,
+}
+
+/// Cw721HookMsg should be de/serialized under `Cw721Hook()` variant in a ExecuteMsg
+#[cw_serde]
+pub struct Cw721HookMsg {
+    pub token_id: String,
+    /// Previous owner, none on mint
+    pub from: Option<String>,
+    /// New owner, none on burn
+    pub to: Option<String>,
+    pub action: Cw721HookAction,
+}
+
+impl Cw721HookMsg {
+    /// serializes the message
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        let msg = HookExecuteMsg::Cw721Hook(self);
+        to_json_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<TAddress: Into<String>, TCustomResponseMessage>(
+        self,
+        contract_addr: TAddress,
+    ) -> StdResult<CosmosMsg<TCustomResponseMessage>>
+    where
+        TCustomResponseMessage: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let msg = self.into_json_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+
+    /// creates a submessage for each registered hook.
+    /// Failing hooks with `HookErrorPolicy::Ignore` are handled in reply with `HOOK_REPLY_ID`.
+    pub fn into_submsgs<TCustomResponseMessage>(
+        self,
+        storage: &dyn Storage,
+    ) -> StdResult<Vec<SubMsg<TCustomResponseMessage>>>
+    where
+        TCustomResponseMessage: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let config = Cw721Config::<Empty, Empty, Empty>::default();
+        config
+            .hooks
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (hook, error_policy) = item?;
+                let msg = self.clone().into_cosmos_msg(hook)?;
+                Ok(match error_policy {
+                    HookErrorPolicy::Revert => SubMsg::new(msg),
+                    HookErrorPolicy::Ignore => SubMsg::reply_on_error(msg, HOOK_REPLY_ID),
+                })
+            })
+            .collect()
+    }
+}
+
+/// This is just a helper to properly serialize the above message.
+/// The actual hook contract should include this variant in the larger ExecuteMsg enum
+#[cw_serde]
+enum HookExecuteMsg {
+    Cw721Hook(Cw721HookMsg),
+}
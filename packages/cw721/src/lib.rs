@@ -1,10 +1,12 @@
 pub mod error;
 pub mod execute;
 pub mod helpers;
+pub mod hooks;
 pub mod msg;
 pub mod query;
 pub mod receiver;
 pub mod state;
+pub mod transfer_policy;
 
 pub use cw_utils::Expiration;
 pub use state::Approval;
@@ -1,9 +1,10 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp};
 use cw_ownable::{Action, Ownership};
 use cw_utils::Expiration;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 
-use crate::state::CollectionInfo;
+use crate::state::{CollectionInfo, Hook, HookErrorPolicy, MetadataValidation, RecipientPolicy};
 use crate::Approval;
 
 #[cw_serde]
@@ -69,6 +70,14 @@ pub enum Cw721ExecuteMsg<TMetadataExtension, TMetadataExtensionMsg> {
         msg: TMetadataExtensionMsg,
     },
 
+    /// Replaces the token uri and extension of a minted NFT, validated as on mint.
+    /// Only minter can call this.
+    UpdateNftInfo {
+        token_id: String,
+        token_uri: Option<String>,
+        extension: TMetadataExtension,
+    },
+
     /// Sets address to send withdrawn fees to. Only owner can call this.
     SetWithdrawAddress {
         address: String,
@@ -80,6 +89,130 @@ pub enum Cw721ExecuteMsg<TMetadataExtension, TMetadataExtensionMsg> {
     WithdrawFunds {
         amount: Coin,
     },
+
+    /// Sets the rules for validating the default metadata extension on mint and on metadata updates.
+    /// Only owner can call this.
+    UpdateMetadataValidation {
+        validation: MetadataValidation,
+    },
+
+    /// Sets the policy for recipients on mint, transfer and send. Only owner can call this.
+    SetRecipientPolicy {
+        policy: RecipientPolicy,
+    },
+    /// Adds and removes addresses from the recipient allowlist. Only owner can call this.
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Adds and removes addresses from the recipient blocklist. Only owner can call this.
+    UpdateBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    /// Registers a contract notified with a `Cw721HookMsg` on mint, transfer, send and burn.
+    /// At most `MAX_HOOKS` can be registered. Only owner can call this.
+    AddHook {
+        address: String,
+        error_policy: HookErrorPolicy,
+    },
+    /// Unregisters a hook contract. Only owner can call this.
+    RemoveHook {
+        address: String,
+    },
+
+    /// Sets the contract allowing or denying transfers and sends, `None` removes it.
+    /// Only owner can call this.
+    SetTransferPolicy {
+        address: Option<String>,
+    },
+
+    /// Continues the collection stats migration started on migrate, counting up to `limit` tokens.
+    /// Anyone can call this.
+    MigrateCollectionStats {
+        limit: Option<u32>,
+    },
+
+    /// Grants usage rights of a token to a user until expiration, without ownership or transfer rights.
+    /// `None` removes the user. Owner, approved spenders and operators can call this.
+    /// The user is removed on transfer, unless `clear_user_on_transfer` is disabled on instantiate.
+    SetUser {
+        token_id: String,
+        user: Option<String>,
+        expires: Option<Expiration>,
+    },
+
+    /// Moves a token to the recipient, regardless of approvals, recipient and transfer policies.
+    /// Only owner can call this and only if clawback is enabled.
+    ForceTransfer {
+        token_id: String,
+        recipient: String,
+        reason: String,
+    },
+    /// Burns a token, regardless of approvals.
+    /// Only owner can call this and only if clawback is enabled.
+    ForceBurn {
+        token_id: String,
+        reason: String,
+    },
+
+    /// Applies an approval signed off-chain by the owner, so the relayer pays the fees instead.
+    /// `signature` is the secp256k1 signature of the ADR-036 sign doc with the JSON serialized
+    /// payload as data, as produced by wallets' `signArbitrary`. The owner is derived from the
+    /// compressed public key as Cosmos SDK address, so `eth_secp256k1` accounts are not supported.
+    /// Anyone can call this.
+    Permit {
+        payload: PermitPayload,
+        pub_key: Binary,
+        signature: Binary,
+    },
+    /// Invalidates all unused permits of the sender up to the given nonce.
+    RevokePermit {
+        nonce: u64,
+    },
+
+    /// Sets the compressed secp256k1 public key signing mint vouchers, `None` disables vouchers.
+    /// Only minter can call this.
+    SetVoucherSigner {
+        pub_key: Option<Binary>,
+    },
+    /// Mints the token of a voucher to the sender, who pays its price. `signature` is the secp256k1
+    /// signature of the sha256 hash of the JSON serialized voucher by the voucher signer.
+    RedeemVoucher {
+        voucher: MintVoucher,
+        extension: TMetadataExtension,
+        signature: Binary,
+    },
+}
+
+/// Approval signed by the owner, see `Cw721ExecuteMsg::Permit`
+#[cw_serde]
+pub struct PermitPayload {
+    /// Token the spender is approved for, `None` approves the spender as operator of all tokens
+    pub token_id: Option<String>,
+    pub spender: String,
+    pub expires: Option<Expiration>,
+    /// Must match the current nonce of the owner, see `Cw721QueryMsg::PermitNonce`
+    pub nonce: u64,
+    pub chain_id: String,
+    pub contract_address: String,
+}
+
+/// Mint signed off-chain by the voucher signer, see `Cw721ExecuteMsg::RedeemVoucher`
+#[cw_serde]
+pub struct MintVoucher {
+    pub token_id: String,
+    pub token_uri: Option<String>,
+    /// sha256 hash of the JSON serialized extension
+    pub extension_hash: Binary,
+    /// Paid by the buyer, zero for free mints
+    pub price: Coin,
+    pub expires: Expiration,
+    /// Unique per voucher, vouchers can only be redeemed once
+    pub nonce: u64,
+    pub chain_id: String,
+    pub contract_address: String,
 }
 
 #[cw_serde]
@@ -95,11 +228,23 @@ pub struct Cw721InstantiateMsg {
     pub minter: Option<String>,
 
     pub withdraw_address: Option<String>,
+
+    /// Records owner changes per height, enabling `OwnerOfAtHeight` and `TokensAtHeight` queries.
+    /// Can only be set on instantiate, defaults to false.
+    pub snapshot_ownership: Option<bool>,
+
+    /// Allows the creator to force transfer and force burn any token.
+    /// Can only be set on instantiate and never be turned on later, defaults to false.
+    pub clawback_enabled: Option<bool>,
+
+    /// Removes the user of a token on transfer and send as in ERC-4907, defaults to true.
+    /// Set to false to keep the user, e.g. for rentals surviving a sale.
+    pub clear_user_on_transfer: Option<bool>,
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum Cw721QueryMsg<TMetadataExtension> {
+pub enum Cw721QueryMsg<TMetadataExtension, TQueryExtensionMsg> {
     /// Return the owner of the given token, error if token does not exist
     #[returns(OwnerOfResponse)]
     OwnerOf {
@@ -140,6 +285,22 @@ pub enum Cw721QueryMsg<TMetadataExtension> {
     #[returns(NumTokensResponse)]
     NumTokens {},
 
+    /// Minted, burned and holder counts of the collection
+    #[returns(CollectionStatsResponse)]
+    CollectionStats {},
+
+    /// Number of tokens owned by the given address
+    #[returns(BalanceResponse)]
+    BalanceOf { owner: String },
+
+    /// Lists distinct owners with their balances, in lexicographical ordering.
+    /// Incomplete while the collection stats migration is pending.
+    #[returns(OwnersResponse)]
+    AllOwners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     #[returns(CollectionInfo)]
     ContractInfo {},
 
@@ -184,12 +345,108 @@ pub enum Cw721QueryMsg<TMetadataExtension> {
     #[returns(Option<String>)]
     GetWithdrawAddress {},
 
-    // -- below queries, Extension and GetCollectionInfoExtension, are just dummies, since type annotations are required for
-    // -- TMetadataExtension and TCollectionInfoExtension, Error:
+    /// Rules for validating the default metadata extension
+    #[returns(MetadataValidation)]
+    GetMetadataValidation {},
+
+    /// Policy for recipients on mint, transfer and send
+    #[returns(RecipientPolicy)]
+    GetRecipientPolicy {},
+
+    /// Lists addresses in the recipient allowlist, in lexicographical ordering.
+    #[returns(AddressesResponse)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists addresses in the recipient blocklist, in lexicographical ordering.
+    #[returns(AddressesResponse)]
+    Blocklist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists registered hook contracts, in lexicographical ordering.
+    #[returns(HooksResponse)]
+    Hooks {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Contract allowing or denying transfers and sends
+    #[returns(Option<String>)]
+    GetTransferPolicy {},
+
+    /// Whether the creator can force transfer and force burn tokens
+    #[returns(bool)]
+    GetClawbackEnabled {},
+
+    /// User of a token, none if not set or expired
+    #[returns(UserOfResponse)]
+    UserOf { token_id: String },
+
+    /// Tokens the given address is a non-expired user of, in lexicographical ordering.
+    #[returns(TokensResponse)]
+    UserTokens {
+        user: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Public key signing mint vouchers, if set
+    #[returns(Option<Binary>)]
+    GetVoucherSigner {},
+
+    /// Whether the voucher with the given nonce has been redeemed
+    #[returns(bool)]
+    IsVoucherRedeemed { nonce: u64 },
+
+    /// Nonce the next permit signed by the owner must use
+    #[returns(PermitNonceResponse)]
+    PermitNonce { owner: String },
+
+    /// Owner of a token at the beginning of the given block height, `None` if it did not exist.
+    /// Requires `snapshot_ownership` to be enabled on instantiate.
+    #[returns(OwnerOfAtHeightResponse)]
+    OwnerOfAtHeight { token_id: String, height: u64 },
+
+    /// Tokens owned by an owner at the beginning of the given block height.
+    /// Scans at most `limit` tokens ever held by the owner, continue with `next_start_after`.
+    /// Requires `snapshot_ownership` to be enabled on instantiate.
+    #[returns(TokensAtHeightResponse)]
+    TokensAtHeight {
+        owner: String,
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Custom query of a contract extending cw721, handled by `Cw721Query::query_extension`
+    #[returns(ExtensionQueryResponse)]
+    Extension { msg: TQueryExtensionMsg },
+
+    // -- below query, GetMetadataExtension, is just a dummy, since type annotations are required for
+    // -- TMetadataExtension, Error:
     // -- "type annotations needed: cannot infer type for type parameter `TMetadataExtension` declared on the enum `Cw721QueryMsg`"
-    /// Do not use - dummy extension query, needed for inferring type parameter during compile
+    /// Do not use - dummy query, needed for inferring type parameter during compile
     #[returns(())]
-    Extension { msg: TMetadataExtension },
+    GetMetadataExtension { msg: TMetadataExtension },
+}
+
+/// Response of `Cw721QueryMsg::Extension`, defined by the query passed in `msg`, so any JSON value.
+/// Contracts accept their custom queries at top level too, giving them typed responses in the schema,
+/// see the `QueryMsg` of `cw2981-royalties`.
+pub struct ExtensionQueryResponse;
+
+impl JsonSchema for ExtensionQueryResponse {
+    fn schema_name() -> String {
+        "ExtensionQueryResponse".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        Schema::Bool(true)
+    }
 }
 
 #[cw_serde]
@@ -214,6 +471,32 @@ pub struct OwnerOfResponse {
     pub approvals: Vec<Approval>,
 }
 
+#[cw_serde]
+pub struct OwnerOfAtHeightResponse {
+    /// Owner of the token at the given height, none if not minted yet or burned
+    pub owner: Option<String>,
+}
+
+#[cw_serde]
+pub struct TokensAtHeightResponse {
+    /// Tokens owned at the given height within the scanned page
+    pub tokens: Vec<String>,
+    /// Last scanned token, none if all tokens have been scanned.
+    /// May be set even if `tokens` is empty.
+    pub next_start_after: Option<String>,
+}
+
+#[cw_serde]
+pub struct PermitNonceResponse {
+    pub nonce: u64,
+}
+
+#[cw_serde]
+pub struct UserOfResponse {
+    pub user: Option<String>,
+    pub expires: Option<Expiration>,
+}
+
 #[cw_serde]
 pub struct ApprovalResponse {
     pub approval: Approval,
@@ -239,6 +522,38 @@ pub struct NumTokensResponse {
     pub count: u64,
 }
 
+#[cw_serde]
+pub struct CollectionStatsResponse {
+    /// Current supply, same as `NumTokens`
+    pub num_tokens: u64,
+    /// Tokens ever minted, including burned ones
+    pub total_minted: u64,
+    pub total_burned: u64,
+    /// Number of owners holding at least one token
+    pub num_holders: u64,
+    pub last_mint: Option<Timestamp>,
+    /// True while the collection stats migration is pending, holders are not fully counted yet
+    pub migration_pending: bool,
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub balance: u64,
+}
+
+#[cw_serde]
+pub struct OwnerBalance {
+    pub owner: String,
+    pub balance: u64,
+}
+
+#[cw_serde]
+pub struct OwnersResponse {
+    /// If there are more than `limit`, use `start_after` in future queries
+    /// to achieve pagination.
+    pub owners: Vec<OwnerBalance>,
+}
+
 #[cw_serde]
 pub struct NftInfoResponse<TMetadataExtension> {
     /// Universal resource identifier for this NFT
@@ -265,6 +580,18 @@ pub struct TokensResponse {
     pub tokens: Vec<String>,
 }
 
+#[cw_serde]
+pub struct AddressesResponse {
+    /// If there are more than `limit`, use `start_after` in future queries
+    /// to achieve pagination.
+    pub addresses: Vec<String>,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<Hook>,
+}
+
 /// Deprecated: use Cw721QueryMsg::GetMinterOwnership instead!
 /// Shows who can mint these tokens.
 #[cw_serde]
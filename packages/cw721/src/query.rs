@@ -2,18 +2,23 @@ use cosmwasm_std::{
     to_json_binary, Addr, Binary, BlockInfo, Deps, Empty, Env, Order, StdError, StdResult, Storage,
 };
 use cw_ownable::Ownership;
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, Map};
 use cw_utils::{maybe_addr, Expiration};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::{
     msg::{
-        AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, Cw721QueryMsg, MinterResponse,
-        NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
-        TokensResponse,
+        AddressesResponse, AllNftInfoResponse, ApprovalResponse, ApprovalsResponse,
+        BalanceResponse, CollectionStatsResponse, Cw721QueryMsg, HooksResponse, MinterResponse,
+        NftInfoResponse, NumTokensResponse, OperatorResponse, OperatorsResponse, OwnerBalance,
+        OwnerOfAtHeightResponse, OwnerOfResponse, OwnersResponse, PermitNonceResponse,
+        TokensAtHeightResponse, TokensResponse, UserOfResponse,
+    },
+    state::{
+        Approval, CollectionInfo, Cw721Config, Hook, MetadataValidation, NftInfo, RecipientPolicy,
+        MINTER,
     },
-    state::{Approval, CollectionInfo, Cw721Config, NftInfo, MINTER},
 };
 
 pub const DEFAULT_LIMIT: u32 = 10;
@@ -22,6 +27,8 @@ pub const MAX_LIMIT: u32 = 1000;
 pub trait Cw721Query<
     // Metadata defined in NftInfo.
     TMetadataExtension,
+    // Message passed for custom queries, handled by `query_extension`.
+    TQueryExtensionMsg,
 > where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
 {
@@ -29,7 +36,7 @@ pub trait Cw721Query<
         &self,
         deps: Deps,
         env: Env,
-        msg: Cw721QueryMsg<TMetadataExtension>,
+        msg: Cw721QueryMsg<TMetadataExtension, TQueryExtensionMsg>,
     ) -> StdResult<Binary> {
         match msg {
             Cw721QueryMsg::Minter {} => to_json_binary(&self.query_minter(deps.storage)?),
@@ -82,6 +89,15 @@ pub trait Cw721Query<
                 limit,
             )?),
             Cw721QueryMsg::NumTokens {} => to_json_binary(&self.query_num_tokens(deps, env)?),
+            Cw721QueryMsg::CollectionStats {} => {
+                to_json_binary(&self.query_collection_stats(deps, env)?)
+            }
+            Cw721QueryMsg::BalanceOf { owner } => {
+                to_json_binary(&self.query_balance_of(deps, env, owner)?)
+            }
+            Cw721QueryMsg::AllOwners { start_after, limit } => {
+                to_json_binary(&self.query_all_owners(deps, env, start_after, limit)?)
+            }
             Cw721QueryMsg::Tokens {
                 owner,
                 start_after,
@@ -113,12 +129,64 @@ pub trait Cw721Query<
             Cw721QueryMsg::Ownership {} => {
                 to_json_binary(&self.query_minter_ownership(deps.storage)?)
             }
-            Cw721QueryMsg::Extension { msg } => {
-                to_json_binary(&self.query_extension(deps, env, msg)?)
-            }
+            Cw721QueryMsg::Extension { msg } => self.query_extension(deps, env, msg),
+            Cw721QueryMsg::GetMetadataExtension { .. } => Err(StdError::generic_err(
+                "GetMetadataExtension is a dummy query and not supported",
+            )),
             Cw721QueryMsg::GetWithdrawAddress {} => {
                 to_json_binary(&self.query_withdraw_address(deps)?)
             }
+            Cw721QueryMsg::GetMetadataValidation {} => {
+                to_json_binary(&self.query_metadata_validation(deps)?)
+            }
+            Cw721QueryMsg::GetRecipientPolicy {} => {
+                to_json_binary(&self.query_recipient_policy(deps)?)
+            }
+            Cw721QueryMsg::Allowlist { start_after, limit } => {
+                to_json_binary(&self.query_allowlist(deps, start_after, limit)?)
+            }
+            Cw721QueryMsg::Blocklist { start_after, limit } => {
+                to_json_binary(&self.query_blocklist(deps, start_after, limit)?)
+            }
+            Cw721QueryMsg::Hooks { start_after, limit } => {
+                to_json_binary(&self.query_hooks(deps, start_after, limit)?)
+            }
+            Cw721QueryMsg::GetTransferPolicy {} => {
+                to_json_binary(&self.query_transfer_policy(deps)?)
+            }
+            Cw721QueryMsg::GetClawbackEnabled {} => {
+                to_json_binary(&self.query_clawback_enabled(deps)?)
+            }
+            Cw721QueryMsg::UserOf { token_id } => {
+                to_json_binary(&self.query_user_of(deps, env, token_id)?)
+            }
+            Cw721QueryMsg::UserTokens {
+                user,
+                start_after,
+                limit,
+            } => to_json_binary(&self.query_user_tokens(deps, env, user, start_after, limit)?),
+            Cw721QueryMsg::GetVoucherSigner {} => to_json_binary(&self.query_voucher_signer(deps)?),
+            Cw721QueryMsg::IsVoucherRedeemed { nonce } => {
+                to_json_binary(&self.query_voucher_redeemed(deps, nonce)?)
+            }
+            Cw721QueryMsg::PermitNonce { owner } => {
+                to_json_binary(&self.query_permit_nonce(deps, owner)?)
+            }
+            Cw721QueryMsg::OwnerOfAtHeight { token_id, height } => {
+                to_json_binary(&self.query_owner_of_at_height(deps, token_id, height)?)
+            }
+            Cw721QueryMsg::TokensAtHeight {
+                owner,
+                height,
+                start_after,
+                limit,
+            } => to_json_binary(&self.query_tokens_at_height(
+                deps,
+                owner,
+                height,
+                start_after,
+                limit,
+            )?),
         }
     }
 
@@ -147,6 +215,51 @@ pub trait Cw721Query<
         Ok(NumTokensResponse { count })
     }
 
+    fn query_collection_stats(&self, deps: Deps, _env: Env) -> StdResult<CollectionStatsResponse> {
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        Ok(CollectionStatsResponse {
+            num_tokens: config.token_count(deps.storage)?,
+            total_minted: config.total_minted(deps.storage)?,
+            total_burned: config.total_burned(deps.storage)?,
+            num_holders: config.num_holders(deps.storage)?,
+            last_mint: config.last_mint.may_load(deps.storage)?,
+            migration_pending: config.stats_migration.exists(deps.storage),
+        })
+    }
+
+    fn query_balance_of(&self, deps: Deps, _env: Env, owner: String) -> StdResult<BalanceResponse> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let balance = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .balance(deps.storage, &owner_addr)?;
+        Ok(BalanceResponse { balance })
+    }
+
+    fn query_all_owners(
+        &self,
+        deps: Deps,
+        _env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OwnersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let owners = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .balances
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(owner, balance)| OwnerBalance {
+                    owner: owner.to_string(),
+                    balance,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(OwnersResponse { owners })
+    }
+
     fn query_nft_info(
         &self,
         deps: Deps,
@@ -372,14 +485,15 @@ pub trait Cw721Query<
         })
     }
 
-    /// No-op returning empty Binary
+    /// Hook for custom queries of contracts extending cw721, override to handle `TQueryExtensionMsg`.
+    /// Returns the serialized response. Default rejects all extension queries.
     fn query_extension(
         &self,
         _deps: Deps,
         _env: Env,
-        _msg: TMetadataExtension,
+        _msg: TQueryExtensionMsg,
     ) -> StdResult<Binary> {
-        Ok(Binary::default())
+        Err(StdError::generic_err("extension query not supported"))
     }
 
     fn query_withdraw_address(&self, deps: Deps) -> StdResult<Option<String>> {
@@ -387,6 +501,222 @@ pub trait Cw721Query<
             .withdraw_address
             .may_load(deps.storage)
     }
+
+    fn query_metadata_validation(&self, deps: Deps) -> StdResult<MetadataValidation> {
+        Cw721Config::<TMetadataExtension, Empty, Empty>::default().metadata_validation(deps.storage)
+    }
+
+    fn query_recipient_policy(&self, deps: Deps) -> StdResult<RecipientPolicy> {
+        Cw721Config::<TMetadataExtension, Empty, Empty>::default().recipient_policy(deps.storage)
+    }
+
+    fn query_allowlist(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AddressesResponse> {
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        query_address_list(deps, &config.allowlist, start_after, limit)
+    }
+
+    fn query_blocklist(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AddressesResponse> {
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        query_address_list(deps, &config.blocklist, start_after, limit)
+    }
+
+    fn query_hooks(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HooksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let hooks = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .hooks
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(address, error_policy)| Hook {
+                    address,
+                    error_policy,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(HooksResponse { hooks })
+    }
+
+    fn query_transfer_policy(&self, deps: Deps) -> StdResult<Option<String>> {
+        Ok(Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .transfer_policy
+            .may_load(deps.storage)?
+            .map(|addr| addr.to_string()))
+    }
+
+    fn query_clawback_enabled(&self, deps: Deps) -> StdResult<bool> {
+        Cw721Config::<TMetadataExtension, Empty, Empty>::default().clawback_enabled(deps.storage)
+    }
+
+    fn query_voucher_signer(&self, deps: Deps) -> StdResult<Option<Binary>> {
+        Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .voucher_signer
+            .may_load(deps.storage)
+    }
+
+    fn query_voucher_redeemed(&self, deps: Deps, nonce: u64) -> StdResult<bool> {
+        Ok(Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .redeemed_vouchers
+            .has(deps.storage, nonce))
+    }
+
+    fn query_permit_nonce(&self, deps: Deps, owner: String) -> StdResult<PermitNonceResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let nonce = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .permit_nonce(deps.storage, &owner)?;
+        Ok(PermitNonceResponse { nonce })
+    }
+
+    fn query_user_of(&self, deps: Deps, env: Env, token_id: String) -> StdResult<UserOfResponse> {
+        let token_user = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .token_users
+            .may_load(deps.storage, &token_id)?
+            .filter(|token_user| !token_user.is_expired(&env.block));
+        Ok(UserOfResponse {
+            user: token_user.as_ref().map(|u| u.user.to_string()),
+            expires: token_user.map(|u| u.expires),
+        })
+    }
+
+    fn query_user_tokens(
+        &self,
+        deps: Deps,
+        env: Env,
+        user: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let user_addr = deps.api.addr_validate(&user)?;
+        let tokens = Cw721Config::<TMetadataExtension, Empty, Empty>::default()
+            .token_users
+            .idx
+            .user
+            .prefix(user_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, token_user)) => !token_user.is_expired(&env.block),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(token_id, _)| token_id))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    /// Owner at the beginning of `height`, changes made within that block are not included.
+    fn query_owner_of_at_height(
+        &self,
+        deps: Deps,
+        token_id: String,
+        height: u64,
+    ) -> StdResult<OwnerOfAtHeightResponse> {
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        assert_snapshot_ownership(deps.storage, &config)?;
+        let owner = config
+            .owner_snapshots
+            .may_load_at_height(deps.storage, &token_id, height)?;
+        Ok(OwnerOfAtHeightResponse {
+            owner: owner.map(|addr| addr.to_string()),
+        })
+    }
+
+    /// Tokens owned at the beginning of `height`, changes made within that block are not included.
+    fn query_tokens_at_height(
+        &self,
+        deps: Deps,
+        owner: String,
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensAtHeightResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let config = Cw721Config::<TMetadataExtension, Empty, Empty>::default();
+        assert_snapshot_ownership(deps.storage, &config)?;
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        // scan a page of tokens ever held by owner, check each against its snapshotted owner
+        let scanned = config
+            .token_holders
+            .prefix(&owner_addr)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        let next_start_after = if scanned.len() == limit {
+            scanned.last().cloned()
+        } else {
+            None
+        };
+        let mut tokens = vec![];
+        for token_id in scanned {
+            let owner =
+                config
+                    .owner_snapshots
+                    .may_load_at_height(deps.storage, &token_id, height)?;
+            if owner.as_ref() == Some(&owner_addr) {
+                tokens.push(token_id);
+            }
+        }
+
+        Ok(TokensAtHeightResponse {
+            tokens,
+            next_start_after,
+        })
+    }
+}
+
+fn assert_snapshot_ownership<TMetadataExtension>(
+    storage: &dyn Storage,
+    config: &Cw721Config<TMetadataExtension, Empty, Empty>,
+) -> StdResult<()>
+where
+    TMetadataExtension: Serialize + DeserializeOwned + Clone,
+{
+    if !config.snapshot_ownership(storage)? {
+        return Err(StdError::generic_err("Ownership snapshots are not enabled"));
+    }
+    Ok(())
+}
+
+fn query_address_list(
+    deps: Deps,
+    list: &Map<&Addr, Empty>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AddressesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let addresses = list
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|addr| addr.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AddressesResponse { addresses })
 }
 
 pub fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<Approval> {
@@ -1,13 +1,15 @@
 use std::marker::PhantomData;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage};
+use cosmwasm_std::{Addr, Binary, BlockInfo, CustomMsg, Empty, StdResult, Storage, Timestamp};
 use cw_ownable::{OwnershipStore, OWNERSHIP_KEY};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotMap, Strategy};
 use cw_utils::Expiration;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::error::Cw721ContractError;
+
 /// - minter is stored in the contract storage using cw_ownable::OwnershipStore (same as for OWNERSHIP but with different key)
 pub const MINTER: OwnershipStore = OwnershipStore::new(OWNERSHIP_KEY);
 
@@ -29,12 +31,53 @@ pub struct Cw721Config<
     /// Note: replaces deprecated/legacy key "nft_info"!
     pub collection_info: Item<'a, CollectionInfo>,
     pub token_count: Item<'a, u64>,
+    /// Number of tokens ever minted, including burned ones.
+    pub total_minted: Item<'a, u64>,
+    pub total_burned: Item<'a, u64>,
+    /// Number of owners holding at least one token.
+    pub num_holders: Item<'a, u64>,
+    pub last_mint: Item<'a, Timestamp>,
+    /// Last token counted by the collection stats migration, set while the migration is pending.
+    /// Balances of tokens not yet counted are left untouched on mint, transfer and burn.
+    pub stats_migration: Item<'a, Option<String>>,
+    /// Number of tokens held by each owner, owners without tokens are removed.
+    pub balances: Map<'a, &'a Addr, u64>,
     /// Stored as (granter, operator) giving operator full control over granter's account.
     /// NOTE: granter is the owner, so operator has only control for NFTs owned by granter!
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub nft_info:
         IndexedMap<'a, &'a str, NftInfo<TMetadataExtension>, TokenIndexes<'a, TMetadataExtension>>,
     pub withdraw_address: Item<'a, String>,
+    /// Rules for validating `DefaultOptionMetadataExtension`, defaults are used if not set.
+    pub metadata_validation: Item<'a, MetadataValidation>,
+    /// Policy for recipients on mint, transfer and send, `RecipientPolicy::Open` if not set.
+    pub recipient_policy: Item<'a, RecipientPolicy>,
+    /// Recipients allowed in `RecipientPolicy::AllowlistOnly` mode.
+    pub allowlist: Map<'a, &'a Addr, Empty>,
+    /// Recipients rejected in `RecipientPolicy::Blocklist` mode.
+    pub blocklist: Map<'a, &'a Addr, Empty>,
+    /// Contracts notified on mint, transfer, send and burn.
+    pub hooks: Map<'a, &'a Addr, HookErrorPolicy>,
+    /// Contract allowing or denying transfers and sends, see `TransferPolicyQueryMsg`.
+    pub transfer_policy: Item<'a, Addr>,
+    /// Whether owner changes are snapshotted per height, can only be set on instantiate.
+    pub snapshot_ownership: Item<'a, bool>,
+    /// Whether the creator can force transfer and force burn tokens, can only be set on instantiate.
+    pub clawback_enabled: Item<'a, bool>,
+    /// Users of tokens, stored apart from approvals since they have no transfer rights.
+    pub token_users: IndexedMap<'a, &'a str, TokenUser, TokenUserIndexes<'a>>,
+    /// Whether the user of a token is removed on transfer and send, can only be set on instantiate.
+    pub clear_user_on_transfer: Item<'a, bool>,
+    /// Owner of each token, snapshotted on every block it changes.
+    pub owner_snapshots: SnapshotMap<'a, &'a str, Addr>,
+    /// Every (owner, token) pair that ever existed, used for listing tokens of an owner at a height.
+    pub token_holders: Map<'a, (&'a Addr, &'a str), Empty>,
+    /// Nonce of the next permit signed by each owner, preventing replays.
+    pub permit_nonces: Map<'a, &'a Addr, u64>,
+    /// Public key signing mint vouchers.
+    pub voucher_signer: Item<'a, Binary>,
+    /// Nonces of redeemed mint vouchers.
+    pub redeemed_vouchers: Map<'a, u64, Empty>,
 
     pub(crate) _custom_response: PhantomData<TCustomResponseMessage>,
     pub(crate) _custom_execute: PhantomData<TMetadataExtensionMsg>,
@@ -50,10 +93,34 @@ where
         Self::new(
             "collection_info", // Note: replaces deprecated/legacy key "nft_info"
             "num_tokens",
+            "total_minted",
+            "total_burned",
+            "num_holders",
+            "last_mint",
+            "collection_stats_migration",
+            "balances",
             "operators",
             "tokens",
             "tokens__owner",
             "withdraw_address",
+            "metadata_validation",
+            "recipient_policy",
+            "recipient_allowlist",
+            "recipient_blocklist",
+            "hooks",
+            "transfer_policy",
+            "snapshot_ownership",
+            "clawback_enabled",
+            "token_users",
+            "token_users__user",
+            "clear_user_on_transfer",
+            "owner_snapshots",
+            "owner_snapshots__checkpoints",
+            "owner_snapshots__changelog",
+            "token_holders",
+            "permit_nonces",
+            "voucher_signer",
+            "redeemed_vouchers",
         )
     }
 }
@@ -64,23 +131,77 @@ where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         collection_info_key: &'a str,
         token_count_key: &'a str,
+        total_minted_key: &'a str,
+        total_burned_key: &'a str,
+        num_holders_key: &'a str,
+        last_mint_key: &'a str,
+        stats_migration_key: &'a str,
+        balances_key: &'a str,
         operator_key: &'a str,
         nft_info_key: &'a str,
         nft_info_owner_key: &'a str,
         withdraw_address_key: &'a str,
+        metadata_validation_key: &'a str,
+        recipient_policy_key: &'a str,
+        allowlist_key: &'a str,
+        blocklist_key: &'a str,
+        hooks_key: &'a str,
+        transfer_policy_key: &'a str,
+        snapshot_ownership_key: &'a str,
+        clawback_enabled_key: &'a str,
+        token_users_key: &'a str,
+        token_users_user_key: &'a str,
+        clear_user_on_transfer_key: &'a str,
+        owner_snapshots_key: &'a str,
+        owner_snapshots_checkpoints_key: &'a str,
+        owner_snapshots_changelog_key: &'a str,
+        token_holders_key: &'a str,
+        permit_nonces_key: &'a str,
+        voucher_signer_key: &'a str,
+        redeemed_vouchers_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, nft_info_key, nft_info_owner_key),
         };
+        let user_indexes = TokenUserIndexes {
+            user: MultiIndex::new(token_user_idx, token_users_key, token_users_user_key),
+        };
         Self {
             collection_info: Item::new(collection_info_key),
             token_count: Item::new(token_count_key),
+            total_minted: Item::new(total_minted_key),
+            total_burned: Item::new(total_burned_key),
+            num_holders: Item::new(num_holders_key),
+            last_mint: Item::new(last_mint_key),
+            stats_migration: Item::new(stats_migration_key),
+            balances: Map::new(balances_key),
             operators: Map::new(operator_key),
             nft_info: IndexedMap::new(nft_info_key, indexes),
             withdraw_address: Item::new(withdraw_address_key),
+            metadata_validation: Item::new(metadata_validation_key),
+            recipient_policy: Item::new(recipient_policy_key),
+            allowlist: Map::new(allowlist_key),
+            blocklist: Map::new(blocklist_key),
+            hooks: Map::new(hooks_key),
+            transfer_policy: Item::new(transfer_policy_key),
+            snapshot_ownership: Item::new(snapshot_ownership_key),
+            clawback_enabled: Item::new(clawback_enabled_key),
+            token_users: IndexedMap::new(token_users_key, user_indexes),
+            clear_user_on_transfer: Item::new(clear_user_on_transfer_key),
+            owner_snapshots: SnapshotMap::new(
+                owner_snapshots_key,
+                owner_snapshots_checkpoints_key,
+                owner_snapshots_changelog_key,
+                Strategy::EveryBlock,
+            ),
+            token_holders: Map::new(token_holders_key),
+            permit_nonces: Map::new(permit_nonces_key),
+            voucher_signer: Item::new(voucher_signer_key),
+            redeemed_vouchers: Map::new(redeemed_vouchers_key),
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
         }
@@ -101,12 +222,214 @@ where
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
+
+    pub fn total_minted(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.total_minted.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn total_burned(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.total_burned.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn num_holders(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.num_holders.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn record_mint(&self, storage: &mut dyn Storage, time: Timestamp) -> StdResult<u64> {
+        let val = self.total_minted(storage)? + 1;
+        self.total_minted.save(storage, &val)?;
+        self.last_mint.save(storage, &time)?;
+        Ok(val)
+    }
+
+    pub fn record_burn(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let val = self.total_burned(storage)? + 1;
+        self.total_burned.save(storage, &val)?;
+        Ok(val)
+    }
+
+    pub fn permit_nonce(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self
+            .permit_nonces
+            .may_load(storage, owner)?
+            .unwrap_or_default())
+    }
+
+    pub fn balance(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self.balances.may_load(storage, owner)?.unwrap_or_default())
+    }
+
+    pub fn increment_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let val = self.balance(storage, owner)? + 1;
+        self.balances.save(storage, owner, &val)?;
+        if val == 1 {
+            let holders = self.num_holders(storage)? + 1;
+            self.num_holders.save(storage, &holders)?;
+        }
+        Ok(val)
+    }
+
+    /// Saturates at zero, since tokens held before balances were tracked are not counted.
+    pub fn decrement_balance(&self, storage: &mut dyn Storage, owner: &Addr) -> StdResult<u64> {
+        let balance = self.balance(storage, owner)?;
+        let val = balance.saturating_sub(1);
+        if val == 0 {
+            self.balances.remove(storage, owner);
+        } else {
+            self.balances.save(storage, owner, &val)?;
+        }
+        if balance == 1 {
+            let holders = self.num_holders(storage)?.saturating_sub(1);
+            self.num_holders.save(storage, &holders)?;
+        }
+        Ok(val)
+    }
+
+    /// Moves a token between owner balances, `from` is none on mint and `to` is none on burn.
+    /// Tokens not yet counted by a pending collection stats migration are skipped.
+    pub fn update_balances(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        from: Option<&Addr>,
+        to: Option<&Addr>,
+    ) -> StdResult<()> {
+        let counted = match self.stats_migration.may_load(storage)? {
+            None => true,
+            Some(Some(cursor)) => token_id <= cursor.as_str(),
+            Some(None) => false,
+        };
+        if !counted {
+            return Ok(());
+        }
+        if let Some(from) = from {
+            self.decrement_balance(storage, from)?;
+        }
+        if let Some(to) = to {
+            self.increment_balance(storage, to)?;
+        }
+        Ok(())
+    }
+
+    pub fn metadata_validation(&self, storage: &dyn Storage) -> StdResult<MetadataValidation> {
+        Ok(self
+            .metadata_validation
+            .may_load(storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn recipient_policy(&self, storage: &dyn Storage) -> StdResult<RecipientPolicy> {
+        Ok(self.recipient_policy.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn snapshot_ownership(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self
+            .snapshot_ownership
+            .may_load(storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn clawback_enabled(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.clawback_enabled.may_load(storage)?.unwrap_or_default())
+    }
+
+    /// Removes the user of a transferred token, unless configured to keep it.
+    pub fn clear_user_on_transfer(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+    ) -> StdResult<()> {
+        if self
+            .clear_user_on_transfer
+            .may_load(storage)?
+            .unwrap_or(true)
+        {
+            self.token_users.remove(storage, token_id)?;
+        }
+        Ok(())
+    }
+
+    /// Records the new owner of a token at the given height, `None` on burn.
+    /// No-op if ownership snapshots are disabled.
+    pub fn snapshot_owner(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        owner: Option<&Addr>,
+        height: u64,
+    ) -> StdResult<()> {
+        if !self.snapshot_ownership(storage)? {
+            return Ok(());
+        }
+        match owner {
+            Some(owner) => {
+                self.owner_snapshots
+                    .save(storage, token_id, owner, height)?;
+                self.token_holders
+                    .save(storage, (owner, token_id), &Empty {})?;
+            }
+            None => self.owner_snapshots.remove(storage, token_id, height)?,
+        }
+        Ok(())
+    }
+
+    /// Checks the recipient of a mint, transfer or send against the recipient policy.
+    pub fn assert_recipient_allowed(
+        &self,
+        storage: &dyn Storage,
+        recipient: &Addr,
+    ) -> Result<(), Cw721ContractError> {
+        let allowed = match self.recipient_policy(storage)? {
+            RecipientPolicy::Open => true,
+            RecipientPolicy::AllowlistOnly => self.allowlist.has(storage, recipient),
+            RecipientPolicy::Blocklist => !self.blocklist.has(storage, recipient),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(Cw721ContractError::RecipientNotAllowed {
+                recipient: recipient.to_string(),
+            })
+        }
+    }
 }
 
 pub fn token_owner_idx<TMetadataExtension>(_pk: &[u8], d: &NftInfo<TMetadataExtension>) -> Addr {
     d.owner.clone()
 }
 
+pub fn token_user_idx(_pk: &[u8], d: &TokenUser) -> Addr {
+    d.user.clone()
+}
+
+/// Restricts who can receive tokens on mint, transfer and send.
+#[cw_serde]
+#[derive(Default)]
+pub enum RecipientPolicy {
+    /// Any valid address can receive tokens.
+    #[default]
+    Open,
+    /// Only addresses in the allowlist can receive tokens.
+    AllowlistOnly,
+    /// All addresses except those in the blocklist can receive tokens.
+    Blocklist,
+}
+
+/// Defines how a failing hook is handled.
+#[cw_serde]
+pub enum HookErrorPolicy {
+    /// A failing hook reverts the whole action.
+    Revert,
+    /// A failing hook is ignored, the action succeeds.
+    Ignore,
+}
+
+#[cw_serde]
+pub struct Hook {
+    pub address: Addr,
+    pub error_policy: HookErrorPolicy,
+}
+
 #[cw_serde]
 pub struct NftInfo<TMetadataExtension> {
     /// The owner of the newly minted NFT
@@ -123,6 +446,19 @@ pub struct NftInfo<TMetadataExtension> {
     pub extension: TMetadataExtension,
 }
 
+/// Account with usage rights of a token, e.g. a renter. Has no ownership or transfer rights.
+#[cw_serde]
+pub struct TokenUser {
+    pub user: Addr,
+    pub expires: Expiration,
+}
+
+impl TokenUser {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_expired(block)
+    }
+}
+
 #[cw_serde]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -157,6 +493,17 @@ where
     }
 }
 
+pub struct TokenUserIndexes<'a> {
+    pub user: MultiIndex<'a, Addr, TokenUser, String>,
+}
+
+impl<'a> IndexList<TokenUser> for TokenUserIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenUser>> + '_> {
+        let v: Vec<&dyn Index<TokenUser>> = vec![&self.user];
+        Box::new(v.into_iter())
+    }
+}
+
 #[cw_serde]
 pub struct CollectionInfo {
     pub name: String,
@@ -184,3 +531,186 @@ pub struct Trait {
     pub trait_type: String,
     pub value: String,
 }
+
+impl Metadata {
+    /// Checks metadata against the given rules, returns the first violation found.
+    pub fn validate(&self, rules: &MetadataValidation) -> Result<(), Cw721ContractError> {
+        validate_length("name", &self.name, rules.max_field_length)?;
+        validate_length("description", &self.description, rules.max_field_length)?;
+        validate_length("image_data", &self.image_data, rules.max_image_data_length)?;
+        validate_url("image", &self.image, rules)?;
+        validate_url("external_url", &self.external_url, rules)?;
+        validate_url("animation_url", &self.animation_url, rules)?;
+        validate_url("youtube_url", &self.youtube_url, rules)?;
+        if let Some(color) = &self.background_color {
+            if !rules.background_color_format.matches(color) {
+                return Err(Cw721ContractError::InvalidBackgroundColor {
+                    color: color.clone(),
+                });
+            }
+        }
+        if let Some(attributes) = &self.attributes {
+            if attributes.len() > rules.max_attributes as usize {
+                return Err(Cw721ContractError::TooManyAttributes {
+                    max: rules.max_attributes,
+                });
+            }
+            for attribute in attributes {
+                if attribute.trait_type.trim().is_empty() {
+                    return Err(Cw721ContractError::EmptyTraitType {});
+                }
+                validate_length(
+                    "attributes.trait_type",
+                    &Some(attribute.trait_type.clone()),
+                    rules.max_field_length,
+                )?;
+                validate_length(
+                    "attributes.value",
+                    &Some(attribute.value.clone()),
+                    rules.max_field_length,
+                )?;
+                validate_length(
+                    "attributes.display_type",
+                    &attribute.display_type,
+                    rules.max_field_length,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_length(
+    field: &str,
+    value: &Option<String>,
+    max: u32,
+) -> Result<(), Cw721ContractError> {
+    match value {
+        Some(value) if value.len() > max as usize => {
+            Err(Cw721ContractError::MetadataFieldTooLong {
+                field: field.to_string(),
+                max,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_url(
+    field: &str,
+    value: &Option<String>,
+    rules: &MetadataValidation,
+) -> Result<(), Cw721ContractError> {
+    let url = match value {
+        Some(url) => url,
+        None => return Ok(()),
+    };
+    validate_length(field, value, rules.max_field_length)?;
+    let scheme = match url.split_once(':') {
+        Some((scheme, rest)) if !rest.is_empty() && is_valid_scheme(scheme) => {
+            scheme.to_ascii_lowercase()
+        }
+        _ => {
+            return Err(Cw721ContractError::InvalidUrl {
+                field: field.to_string(),
+                url: url.clone(),
+            })
+        }
+    };
+    // empty allowlist accepts any scheme
+    if !rules.allowed_url_schemes.is_empty()
+        && !rules
+            .allowed_url_schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&scheme))
+    {
+        return Err(Cw721ContractError::UrlSchemeNotAllowed {
+            field: field.to_string(),
+            scheme,
+        });
+    }
+    Ok(())
+}
+
+/// Scheme as defined in RFC 3986: ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+fn is_valid_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+}
+
+/// Limits for `DefaultOptionMetadataExtension`, checked on mint and on metadata updates.
+#[cw_serde]
+pub struct MetadataValidation {
+    /// Max length of text and url fields, including attribute fields
+    pub max_field_length: u32,
+    /// Max length of `image_data`, which usually holds raw svg
+    pub max_image_data_length: u32,
+    /// Max number of attributes
+    pub max_attributes: u32,
+    /// Url schemes allowed for `image`, `external_url`, `animation_url` and `youtube_url`, e.g. "ipfs", "https" or "ar".
+    /// An empty list allows any scheme.
+    pub allowed_url_schemes: Vec<String>,
+    pub background_color_format: ColorFormat,
+}
+
+impl MetadataValidation {
+    /// Rejects zero limits and url schemes that can never match, e.g. "https://".
+    pub fn validate(&self) -> Result<(), Cw721ContractError> {
+        for (field, limit) in [
+            ("max_field_length", self.max_field_length),
+            ("max_image_data_length", self.max_image_data_length),
+            ("max_attributes", self.max_attributes),
+        ] {
+            if limit == 0 {
+                return Err(Cw721ContractError::InvalidMetadataValidation {
+                    reason: format!("{field} must be greater than zero"),
+                });
+            }
+        }
+        if let Some(scheme) = self
+            .allowed_url_schemes
+            .iter()
+            .find(|scheme| !is_valid_scheme(scheme))
+        {
+            return Err(Cw721ContractError::InvalidMetadataValidation {
+                reason: format!("invalid url scheme: {scheme}"),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for MetadataValidation {
+    fn default() -> Self {
+        Self {
+            max_field_length: 512,
+            max_image_data_length: 16 * 1024,
+            max_attributes: 64,
+            allowed_url_schemes: vec!["ipfs".to_string(), "https".to_string(), "ar".to_string()],
+            background_color_format: ColorFormat::Hex,
+        }
+    }
+}
+
+#[cw_serde]
+pub enum ColorFormat {
+    /// Six-character hexadecimal without a pre-pended #, as expected by OpenSea (e.g. "1a2b3c")
+    Hex,
+    /// Six-character hexadecimal with a pre-pended # (e.g. "#1a2b3c")
+    HashHex,
+}
+
+impl ColorFormat {
+    pub fn matches(&self, color: &str) -> bool {
+        let hex = match self {
+            ColorFormat::Hex => color,
+            ColorFormat::HashHex => match color.strip_prefix('#') {
+                Some(hex) => hex,
+                None => return false,
+            },
+        };
+        hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use cosmwasm_std::CustomMsg;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -6,16 +8,28 @@ use crate::execute::Cw721Execute;
 use crate::query::Cw721Query;
 use crate::state::Cw721Config;
 
-pub struct Cw721Contract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
-where
+pub struct Cw721Contract<
+    'a,
+    TMetadataExtension,
+    TCustomResponseMessage,
+    TMetadataExtensionMsg,
+    TQueryExtensionMsg,
+> where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
 {
     pub config: Cw721Config<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>,
+    pub(crate) _query_extension: PhantomData<TQueryExtensionMsg>,
 }
 
-impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg> Default
-    for Cw721Contract<'static, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg> Default
+    for Cw721Contract<
+        'static,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TMetadataExtensionMsg: CustomMsg,
@@ -23,13 +37,20 @@ where
     fn default() -> Self {
         Self {
             config: Cw721Config::default(),
+            _query_extension: PhantomData,
         }
     }
 }
 
-impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg>
     Cw721Execute<TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
-    for Cw721Contract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+    for Cw721Contract<
+        'a,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TCustomResponseMessage: CustomMsg,
@@ -37,9 +58,15 @@ where
 {
 }
 
-impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
-    Cw721Query<TMetadataExtension>
-    for Cw721Contract<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg>
+impl<'a, TMetadataExtension, TCustomResponseMessage, TMetadataExtensionMsg, TQueryExtensionMsg>
+    Cw721Query<TMetadataExtension, TQueryExtensionMsg>
+    for Cw721Contract<
+        'a,
+        TMetadataExtension,
+        TCustomResponseMessage,
+        TMetadataExtensionMsg,
+        TQueryExtensionMsg,
+    >
 where
     TMetadataExtension: Serialize + DeserializeOwned + Clone,
     TCustomResponseMessage: CustomMsg,
@@ -1,18 +1,32 @@
 #![cfg(test)]
 
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockQuerier, MockStorage};
 
 use cosmwasm_std::{
-    from_json, to_json_binary, Addr, Coin, CosmosMsg, DepsMut, Empty, Response, StdError, WasmMsg,
+    coin, coins, from_json, to_json_binary, to_json_vec, Addr, Binary, Coin, CosmosMsg, Deps,
+    DepsMut, Empty, Event, OwnedDeps, Reply, Response, StdError, SubMsg, SubMsgResult, WasmMsg,
 };
+use cw_multi_test::addons::MockApiBech32;
+use k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+use sha2::{Digest, Sha256};
 
 use crate::error::Cw721ContractError;
+use crate::execute::derive_address;
+use crate::hooks::{Cw721HookAction, Cw721HookMsg, HOOK_REPLY_ID, MAX_HOOKS};
 use crate::msg::{
-    ApprovalResponse, NftInfoResponse, OperatorResponse, OperatorsResponse, OwnerOfResponse,
+    AddressesResponse, ApprovalResponse, BalanceResponse, CollectionStatsResponse, HooksResponse,
+    MintVoucher, NftInfoResponse, OperatorResponse, OperatorsResponse, OwnerBalance,
+    OwnerOfAtHeightResponse, OwnerOfResponse, OwnersResponse, PermitNonceResponse, PermitPayload,
+    TokensAtHeightResponse, UserOfResponse,
 };
 use crate::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg};
 use crate::receiver::Cw721ReceiveMsg;
-use crate::state::{CollectionInfo, DefaultOptionMetadataExtension, MINTER};
+use crate::state::{
+    CollectionInfo, DefaultOptionMetadataExtension, Hook, HookErrorPolicy, Metadata,
+    RecipientPolicy, MINTER,
+};
 use crate::{execute::Cw721Execute, query::Cw721Query, Approval, Expiration};
 use cw_ownable::{Action, Ownership, OwnershipError};
 
@@ -25,13 +39,16 @@ const SYMBOL: &str = "MGK";
 
 fn setup_contract(
     deps: DepsMut<'_>,
-) -> Cw721Contract<'static, DefaultOptionMetadataExtension, Empty, Empty> {
+) -> Cw721Contract<'static, DefaultOptionMetadataExtension, Empty, Empty, Empty> {
     let contract = Cw721Contract::default();
     let msg = Cw721InstantiateMsg {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: None,
+        snapshot_ownership: None,
+        clawback_enabled: None,
+        clear_user_on_transfer: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract
@@ -51,13 +68,16 @@ fn setup_contract(
 #[test]
 fn proper_instantiation() {
     let mut deps = mock_dependencies();
-    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
 
     let msg = Cw721InstantiateMsg {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: Some(String::from(CREATOR_ADDR)),
+        snapshot_ownership: None,
+        clawback_enabled: None,
+        clear_user_on_transfer: None,
     };
     let info = mock_info("creator", &[]);
     let env = mock_env();
@@ -111,13 +131,16 @@ fn proper_instantiation() {
 #[test]
 fn proper_instantiation_with_collection_info() {
     let mut deps = mock_dependencies();
-    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
 
     let msg = Cw721InstantiateMsg {
         name: CONTRACT_NAME.to_string(),
         symbol: SYMBOL.to_string(),
         minter: Some(String::from(MINTER_ADDR)),
         withdraw_address: Some(String::from(CREATOR_ADDR)),
+        snapshot_ownership: None,
+        clawback_enabled: None,
+        clear_user_on_transfer: None,
     };
     let collection_info = mock_info("creator", &[]);
     let env = mock_env();
@@ -400,6 +423,218 @@ fn burning() {
     assert!(tokens.tokens.is_empty());
 }
 
+#[test]
+fn balance_of() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER_ADDR, &[]);
+
+    for (token_id, owner) in [("1", "venus"), ("2", "venus"), ("3", "mars")] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                minter.clone(),
+                Cw721ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: owner.to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+    let balance = |deps: Deps, owner: &str| {
+        contract
+            .query_balance_of(deps, mock_env(), owner.to_string())
+            .unwrap()
+            .balance
+    };
+    assert_eq!(balance(deps.as_ref(), "venus"), 2);
+    assert_eq!(balance(deps.as_ref(), "mars"), 1);
+    assert_eq!(balance(deps.as_ref(), "random"), 0);
+
+    // transfer updates both balances
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance(deps.as_ref(), "venus"), 1);
+    assert_eq!(balance(deps.as_ref(), "mars"), 2);
+
+    // burn decrements, owners without tokens are removed
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::Burn {
+                token_id: "2".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance(deps.as_ref(), "venus"), 0);
+    assert!(!contract
+        .config
+        .balances
+        .has(deps.as_ref().storage, &Addr::unchecked("venus")));
+
+    // same via query msg
+    let res: BalanceResponse = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::BalanceOf {
+                    owner: "mars".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.balance, 2);
+
+    // distinct owners with balances, owners without tokens are not listed
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mars", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "jupiter".to_string(),
+                token_id: "3".to_string(),
+            },
+        )
+        .unwrap();
+    let owners = contract
+        .query_all_owners(deps.as_ref(), mock_env(), None, None)
+        .unwrap()
+        .owners;
+    assert_eq!(
+        owners,
+        vec![
+            OwnerBalance {
+                owner: "jupiter".to_string(),
+                balance: 1
+            },
+            OwnerBalance {
+                owner: "mars".to_string(),
+                balance: 1
+            },
+        ]
+    );
+
+    // paginate
+    let res: OwnersResponse = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::AllOwners {
+                    start_after: Some("jupiter".to_string()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.owners,
+        vec![OwnerBalance {
+            owner: "mars".to_string(),
+            balance: 1
+        }]
+    );
+}
+
+#[test]
+fn collection_stats() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let stats = contract
+        .query_collection_stats(deps.as_ref(), mock_env())
+        .unwrap();
+    assert_eq!(
+        stats,
+        CollectionStatsResponse {
+            num_tokens: 0,
+            total_minted: 0,
+            total_burned: 0,
+            num_holders: 0,
+            last_mint: None,
+            migration_pending: false,
+        }
+    );
+
+    let mut env = mock_env();
+    for (token_id, owner) in [("1", "venus"), ("2", "venus"), ("3", "mars")] {
+        env.block.time = env.block.time.plus_seconds(10);
+        contract
+            .execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(MINTER_ADDR, &[]),
+                Cw721ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: owner.to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+    let last_mint = env.block.time;
+
+    // transfer to a new holder, then burn the last token of venus
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "jupiter".to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::Burn {
+                token_id: "2".to_string(),
+            },
+        )
+        .unwrap();
+
+    let stats: CollectionStatsResponse = from_json(
+        contract
+            .query(deps.as_ref(), mock_env(), Cw721QueryMsg::CollectionStats {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        stats,
+        CollectionStatsResponse {
+            num_tokens: 2,
+            total_minted: 3,
+            total_burned: 1,
+            num_holders: 2,
+            last_mint: Some(last_mint),
+            migration_pending: false,
+        }
+    );
+}
+
 #[test]
 fn transferring_nft() {
     let mut deps = mock_dependencies();
@@ -521,174 +756,1207 @@ fn sending_nft() {
 }
 
 #[test]
-fn approving_revoking() {
+fn ownership_snapshots() {
     let mut deps = mock_dependencies();
     let contract = setup_contract(deps.as_mut());
 
-    // Mint a token
-    let token_id = "grow".to_string();
-    let token_uri = "https://www.merriam-webster.com/dictionary/grow".to_string();
+    // snapshots are disabled by default
+    let err = contract
+        .query_owner_of_at_height(deps.as_ref(), "1".to_string(), 1)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Ownership snapshots are not enabled")
+    );
 
-    let mint_msg = Cw721ExecuteMsg::Mint {
-        token_id: token_id.clone(),
-        owner: String::from("demeter"),
-        token_uri: Some(token_uri),
-        extension: None,
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    let msg = Cw721InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: Some(String::from(MINTER_ADDR)),
+        withdraw_address: None,
+        snapshot_ownership: Some(true),
+        clawback_enabled: None,
+        clear_user_on_transfer: None,
     };
-
-    let minter = mock_info(MINTER_ADDR, &[]);
     contract
-        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
-        .unwrap();
-
-    // token owner shows in approval query
-    let res = contract
-        .query_approval(
-            deps.as_ref(),
+        .instantiate(
+            deps.as_mut(),
             mock_env(),
-            token_id.clone(),
-            String::from("demeter"),
-            false,
+            mock_info("creator", &[]),
+            msg,
+            "contract_name",
+            "contract_version",
         )
         .unwrap();
-    assert_eq!(
-        res,
-        ApprovalResponse {
-            approval: Approval {
-                spender: Addr::unchecked("demeter"),
-                expires: Expiration::Never {}
-            }
-        }
-    );
 
-    // Give random transferring power
-    let approve_msg = Cw721ExecuteMsg::Approve {
-        spender: String::from("random"),
-        token_id: token_id.clone(),
-        expires: None,
+    let env_at = |height: u64| {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
     };
-    let owner = mock_info("demeter", &[]);
-    let res = contract
-        .execute(deps.as_mut(), mock_env(), owner, approve_msg)
-        .unwrap();
-    assert_eq!(
-        res,
-        Response::new()
-            .add_attribute("action", "approve")
-            .add_attribute("sender", "demeter")
-            .add_attribute("spender", "random")
-            .add_attribute("token_id", token_id.clone())
-    );
 
-    // test approval query
-    let res = contract
-        .query_approval(
-            deps.as_ref(),
-            mock_env(),
-            token_id.clone(),
-            String::from("random"),
-            true,
+    // mint two tokens at height 100
+    for token_id in ["1", "2"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                env_at(100),
+                mock_info(MINTER_ADDR, &[]),
+                Cw721ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: "venus".to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+    // transfer token 1 at height 200
+    contract
+        .execute(
+            deps.as_mut(),
+            env_at(200),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "1".to_string(),
+            },
         )
         .unwrap();
-    assert_eq!(
-        res,
-        ApprovalResponse {
-            approval: Approval {
-                spender: Addr::unchecked("random"),
-                expires: Expiration::Never {}
-            }
-        }
-    );
-
-    // random can now transfer
-    let random = mock_info("random", &[]);
-    let transfer_msg = Cw721ExecuteMsg::TransferNft {
-        recipient: String::from("person"),
-        token_id: token_id.clone(),
-    };
+    // burn token 2 at height 300
     contract
-        .execute(deps.as_mut(), mock_env(), random, transfer_msg)
+        .execute(
+            deps.as_mut(),
+            env_at(300),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::Burn {
+                token_id: "2".to_string(),
+            },
+        )
         .unwrap();
 
-    // Approvals are removed / cleared
-    let query_msg = Cw721QueryMsg::OwnerOf {
-        token_id: token_id.clone(),
-        include_expired: None,
+    // owner at the beginning of a block, changes within that block are not included
+    let owner_at = |token_id: &str, height: u64| {
+        contract
+            .query_owner_of_at_height(deps.as_ref(), token_id.to_string(), height)
+            .unwrap()
+            .owner
     };
-    let res: OwnerOfResponse = from_json(
+    assert_eq!(owner_at("1", 100), None);
+    assert_eq!(owner_at("1", 101), Some("venus".to_string()));
+    assert_eq!(owner_at("1", 200), Some("venus".to_string()));
+    assert_eq!(owner_at("1", 201), Some("mars".to_string()));
+    assert_eq!(owner_at("1", 1000), Some("mars".to_string()));
+    assert_eq!(owner_at("2", 300), Some("venus".to_string()));
+    assert_eq!(owner_at("2", 301), None);
+
+    let tokens_at = |owner: &str, height: u64, start_after: Option<&str>, limit: Option<u32>| {
         contract
-            .query(deps.as_ref(), mock_env(), query_msg.clone())
-            .unwrap(),
-    )
-    .unwrap();
+            .query_tokens_at_height(
+                deps.as_ref(),
+                owner.to_string(),
+                height,
+                start_after.map(String::from),
+                limit,
+            )
+            .unwrap()
+            .tokens
+    };
+    assert!(tokens_at("venus", 100, None, None).is_empty());
+    assert_eq!(tokens_at("venus", 101, None, None), vec!["1", "2"]);
+    assert_eq!(tokens_at("venus", 201, None, None), vec!["2"]);
+    assert!(tokens_at("venus", 301, None, None).is_empty());
+    assert!(tokens_at("mars", 200, None, None).is_empty());
+    assert_eq!(tokens_at("mars", 201, None, None), vec!["1"]);
+
+    // paginate, scanning is bounded by limit and continues after the last scanned token
+    assert_eq!(tokens_at("venus", 101, None, Some(1)), vec!["1"]);
+    assert_eq!(tokens_at("venus", 101, Some("1"), Some(1)), vec!["2"]);
+    let page_at = |start_after: Option<String>| {
+        contract
+            .query_tokens_at_height(
+                deps.as_ref(),
+                "venus".to_string(),
+                201,
+                start_after,
+                Some(1),
+            )
+            .unwrap()
+    };
+    let res = page_at(None);
     assert_eq!(
         res,
-        OwnerOfResponse {
-            owner: String::from("person"),
-            approvals: vec![],
+        TokensAtHeightResponse {
+            tokens: vec![],
+            next_start_after: Some("1".to_string()),
+        }
+    );
+    let res = page_at(res.next_start_after);
+    assert_eq!(
+        res,
+        TokensAtHeightResponse {
+            tokens: vec!["2".to_string()],
+            next_start_after: Some("2".to_string()),
+        }
+    );
+    let res = page_at(res.next_start_after);
+    assert_eq!(
+        res,
+        TokensAtHeightResponse {
+            tokens: vec![],
+            next_start_after: None,
         }
     );
 
-    // Approve, revoke, and check for empty, to test revoke
-    let approve_msg = Cw721ExecuteMsg::Approve {
-        spender: String::from("random"),
-        token_id: token_id.clone(),
-        expires: None,
-    };
-    let owner = mock_info("person", &[]);
-    contract
-        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
-        .unwrap();
-
-    let revoke_msg = Cw721ExecuteMsg::Revoke {
-        spender: String::from("random"),
-        token_id,
-    };
-    contract
-        .execute(deps.as_mut(), mock_env(), owner, revoke_msg)
-        .unwrap();
-
-    // Approvals are now removed / cleared
-    let res: OwnerOfResponse = from_json(
+    // same via query msg
+    let res: OwnerOfAtHeightResponse = from_json(
         contract
-            .query(deps.as_ref(), mock_env(), query_msg)
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::OwnerOfAtHeight {
+                    token_id: "1".to_string(),
+                    height: 150,
+                },
+            )
             .unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        res,
-        OwnerOfResponse {
-            owner: String::from("person"),
-            approvals: vec![],
-        }
-    );
+    assert_eq!(res.owner, Some("venus".to_string()));
 }
 
 #[test]
-fn approving_all_revoking_all() {
+fn clawback() {
     let mut deps = mock_dependencies();
     let contract = setup_contract(deps.as_mut());
-
-    // Mint a couple tokens (from the same owner)
-    let token_id1 = "grow1".to_string();
-    let token_uri1 = "https://www.merriam-webster.com/dictionary/grow1".to_string();
-
-    let token_id2 = "grow2".to_string();
-    let token_uri2 = "https://www.merriam-webster.com/dictionary/grow2".to_string();
-
-    let mint_msg1 = Cw721ExecuteMsg::Mint {
-        token_id: token_id1.clone(),
-        owner: String::from("demeter"),
-        token_uri: Some(token_uri1),
+    let mint_msg = Cw721ExecuteMsg::Mint {
+        token_id: "1".to_string(),
+        owner: "venus".to_string(),
+        token_uri: None,
         extension: None,
     };
-
-    let minter = mock_info(MINTER_ADDR, &[]);
     contract
-        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg1)
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            mint_msg.clone(),
+        )
         .unwrap();
 
-    let mint_msg2 = Cw721ExecuteMsg::Mint {
+    // clawback is disabled by default
+    assert!(!contract.query_clawback_enabled(deps.as_ref()).unwrap());
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            Cw721ExecuteMsg::ForceTransfer {
+                token_id: "1".to_string(),
+                recipient: "treasury".to_string(),
+                reason: "fraud".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::ClawbackDisabled {});
+
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            Cw721InstantiateMsg {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+                minter: Some(String::from(MINTER_ADDR)),
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: Some(true),
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+    let enabled: bool = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::GetClawbackEnabled {},
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(enabled);
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            mint_msg,
+        )
+        .unwrap();
+
+    // only creator can force transfer
+    let force_transfer_msg = Cw721ExecuteMsg::ForceTransfer {
+        token_id: "1".to_string(),
+        recipient: "treasury".to_string(),
+        reason: "court order".to_string(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            force_transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            force_transfer_msg,
+        )
+        .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("cw721_clawback")
+            .add_attribute("action", "force_transfer")
+            .add_attribute("token_id", "1")
+            .add_attribute("from", "venus")
+            .add_attribute("to", "treasury")
+            .add_attribute("reason", "court order")]
+    );
+    let owner = contract
+        .query_owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap()
+        .owner;
+    assert_eq!(owner, "treasury");
+    let balance = contract
+        .query_balance_of(deps.as_ref(), mock_env(), "venus".to_string())
+        .unwrap()
+        .balance;
+    assert_eq!(balance, 0);
+
+    // force burn
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            Cw721ExecuteMsg::ForceBurn {
+                token_id: "1".to_string(),
+                reason: "fraud".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("cw721_clawback")
+            .add_attribute("action", "force_burn")
+            .add_attribute("token_id", "1")
+            .add_attribute("from", "treasury")
+            .add_attribute("reason", "fraud")]
+    );
+    let count = contract
+        .query_num_tokens(deps.as_ref(), mock_env())
+        .unwrap()
+        .count;
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn token_users() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            Cw721InstantiateMsg {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+                minter: Some(String::from(MINTER_ADDR)),
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+    for token_id in ["1", "2"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER_ADDR, &[]),
+                Cw721ExecuteMsg::Mint {
+                    token_id: token_id.to_string(),
+                    owner: "venus".to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+            )
+            .unwrap();
+    }
+
+    // random cannot set user
+    let env = mock_env();
+    let expires = Expiration::AtHeight(env.block.height + 100);
+    let set_user_msg = |token_id: &str, user: Option<&str>| Cw721ExecuteMsg::SetUser {
+        token_id: token_id.to_string(),
+        user: user.map(String::from),
+        expires: Some(expires),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("random", &[]),
+            set_user_msg("1", Some("renter")),
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+
+    // owner and approved spender can set user
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("venus", &[]),
+            set_user_msg("1", Some("renter")),
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::Approve {
+                spender: "market".to_string(),
+                token_id: "2".to_string(),
+                expires: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("market", &[]),
+            set_user_msg("2", Some("renter")),
+        )
+        .unwrap();
+
+    // user has no approval, so cannot transfer
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("renter", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "renter".to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+
+    let res: UserOfResponse = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                env.clone(),
+                Cw721QueryMsg::UserOf {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        UserOfResponse {
+            user: Some("renter".to_string()),
+            expires: Some(expires),
+        }
+    );
+    let tokens = contract
+        .query_user_tokens(deps.as_ref(), env.clone(), "renter".to_string(), None, None)
+        .unwrap();
+    assert_eq!(tokens.tokens, vec!["1", "2"]);
+
+    // expired users are filtered out
+    let mut expired_env = mock_env();
+    expired_env.block.height += 100;
+    let res = contract
+        .query_user_of(deps.as_ref(), expired_env.clone(), "1".to_string())
+        .unwrap();
+    assert_eq!(
+        res,
+        UserOfResponse {
+            user: None,
+            expires: None,
+        }
+    );
+    let tokens = contract
+        .query_user_tokens(deps.as_ref(), expired_env, "renter".to_string(), None, None)
+        .unwrap();
+    assert!(tokens.tokens.is_empty());
+
+    // transfer clears the user by default
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    let res = contract
+        .query_user_of(deps.as_ref(), env.clone(), "1".to_string())
+        .unwrap();
+    assert_eq!(res.user, None);
+
+    // owner removes user
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("venus", &[]),
+            set_user_msg("2", None),
+        )
+        .unwrap();
+    let tokens = contract
+        .query_user_tokens(deps.as_ref(), env, "renter".to_string(), None, None)
+        .unwrap();
+    assert!(tokens.tokens.is_empty());
+}
+
+#[test]
+fn keep_user_on_transfer() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            Cw721InstantiateMsg {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+                minter: Some(String::from(MINTER_ADDR)),
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: Some(false),
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            Cw721ExecuteMsg::Mint {
+                token_id: "1".to_string(),
+                owner: "venus".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::SetUser {
+                token_id: "1".to_string(),
+                user: Some("renter".to_string()),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+    // user survives the transfer
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    let res = contract
+        .query_user_of(deps.as_ref(), mock_env(), "1".to_string())
+        .unwrap();
+    assert_eq!(res.user, Some("renter".to_string()));
+}
+
+#[test]
+fn recipient_policy() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER_ADDR, &[]);
+
+    // default is open
+    let policy = contract.query_recipient_policy(deps.as_ref()).unwrap();
+    assert_eq!(policy, RecipientPolicy::Open);
+
+    // only creator can manage policy and lists
+    let random = mock_info("random", &[]);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            random.clone(),
+            Cw721ExecuteMsg::SetRecipientPolicy {
+                policy: RecipientPolicy::AllowlistOnly,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            random,
+            Cw721ExecuteMsg::UpdateAllowlist {
+                add: vec!["venus".to_string()],
+                remove: vec![],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+
+    // allowlist only: mint and transfer only to listed addresses
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::SetRecipientPolicy {
+                policy: RecipientPolicy::AllowlistOnly,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::UpdateAllowlist {
+                add: vec!["venus".to_string(), "mars".to_string()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::Mint {
+                token_id: "melt".to_string(),
+                owner: "random".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::RecipientNotAllowed {
+            recipient: "random".to_string()
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::Mint {
+                token_id: "melt".to_string(),
+                owner: "venus".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+
+    let venus = mock_info("venus", &[]);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            venus.clone(),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "random".to_string(),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::RecipientNotAllowed {
+            recipient: "random".to_string()
+        }
+    );
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            venus.clone(),
+            Cw721ExecuteMsg::SendNft {
+                contract: "random".to_string(),
+                token_id: "melt".to_string(),
+                msg: to_json_binary("yes").unwrap(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::RecipientNotAllowed {
+            recipient: "random".to_string()
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            venus,
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap();
+
+    // list allowlist, paginated
+    let allowlist: AddressesResponse = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::Allowlist {
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(allowlist.addresses, vec!["mars".to_string()]);
+    let allowlist = contract
+        .query_allowlist(deps.as_ref(), Some("mars".to_string()), None)
+        .unwrap();
+    assert_eq!(allowlist.addresses, vec!["venus".to_string()]);
+
+    // blocklist: all except listed addresses
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::SetRecipientPolicy {
+                policy: RecipientPolicy::Blocklist,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            Cw721ExecuteMsg::UpdateBlocklist {
+                add: vec!["venus".to_string()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    let mars = mock_info("mars", &[]);
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mars.clone(),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "venus".to_string(),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::RecipientNotAllowed {
+            recipient: "venus".to_string()
+        }
+    );
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mars,
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "random".to_string(),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap();
+
+    let blocklist = contract.query_blocklist(deps.as_ref(), None, None).unwrap();
+    assert_eq!(blocklist.addresses, vec!["venus".to_string()]);
+}
+
+#[test]
+fn hooks() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let minter = mock_info(MINTER_ADDR, &[]);
+
+    // only creator can add hooks
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("random", &[]),
+            Cw721ExecuteMsg::AddHook {
+                address: "staking".to_string(),
+                error_policy: HookErrorPolicy::Revert,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::AddHook {
+                address: "staking".to_string(),
+                error_policy: HookErrorPolicy::Revert,
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::AddHook {
+                address: "voting".to_string(),
+                error_policy: HookErrorPolicy::Ignore,
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::AddHook {
+                address: "voting".to_string(),
+                error_policy: HookErrorPolicy::Revert,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::HookAlreadyRegistered {
+            address: "voting".to_string()
+        }
+    );
+
+    let hooks: HooksResponse = from_json(
+        contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                Cw721QueryMsg::Hooks {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        hooks.hooks,
+        vec![
+            Hook {
+                address: Addr::unchecked("staking"),
+                error_policy: HookErrorPolicy::Revert,
+            },
+            Hook {
+                address: Addr::unchecked("voting"),
+                error_policy: HookErrorPolicy::Ignore,
+            },
+        ]
+    );
+
+    let expected_submsgs = |hook_msg: Cw721HookMsg| {
+        vec![
+            SubMsg::new(hook_msg.clone().into_cosmos_msg("staking").unwrap()),
+            SubMsg::reply_on_error(hook_msg.into_cosmos_msg("voting").unwrap(), HOOK_REPLY_ID),
+        ]
+    };
+
+    // mint
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::Mint {
+                token_id: "melt".to_string(),
+                owner: "venus".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        expected_submsgs(Cw721HookMsg {
+            token_id: "melt".to_string(),
+            from: None,
+            to: Some("venus".to_string()),
+            action: Cw721HookAction::Mint,
+        })
+    );
+
+    // transfer
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::TransferNft {
+                recipient: "mars".to_string(),
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        expected_submsgs(Cw721HookMsg {
+            token_id: "melt".to_string(),
+            from: Some("venus".to_string()),
+            to: Some("mars".to_string()),
+            action: Cw721HookAction::Transfer,
+        })
+    );
+
+    // send, hooks are dispatched after the receive message
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("mars", &[]),
+            Cw721ExecuteMsg::SendNft {
+                contract: "venus".to_string(),
+                token_id: "melt".to_string(),
+                msg: to_json_binary("yes").unwrap(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[1..],
+        expected_submsgs(Cw721HookMsg {
+            token_id: "melt".to_string(),
+            from: Some("mars".to_string()),
+            to: Some("venus".to_string()),
+            action: Cw721HookAction::Send,
+        })
+    );
+
+    // burn
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            Cw721ExecuteMsg::Burn {
+                token_id: "melt".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.messages,
+        expected_submsgs(Cw721HookMsg {
+            token_id: "melt".to_string(),
+            from: Some("venus".to_string()),
+            to: None,
+            action: Cw721HookAction::Burn,
+        })
+    );
+
+    // failing ignored hook is handled in reply
+    let res = contract
+        .reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: HOOK_REPLY_ID,
+                result: SubMsgResult::Err("voting failed".to_string()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "hook_failed")
+            .add_attribute("error", "voting failed")
+    );
+
+    // remove hooks
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            Cw721ExecuteMsg::RemoveHook {
+                address: "staking".to_string(),
+            },
+        )
+        .unwrap();
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            minter,
+            Cw721ExecuteMsg::RemoveHook {
+                address: "staking".to_string(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::HookNotRegistered {
+            address: "staking".to_string()
+        }
+    );
+    let hooks = contract.query_hooks(deps.as_ref(), None, None).unwrap();
+    assert_eq!(hooks.hooks.len(), 1);
+
+    // number of hooks is capped
+    let add_hook = |address: String| Cw721ExecuteMsg::AddHook {
+        address,
+        error_policy: HookErrorPolicy::Ignore,
+    };
+    for i in 1..MAX_HOOKS {
+        contract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MINTER_ADDR, &[]),
+                add_hook(format!("hook{i}")),
+            )
+            .unwrap();
+    }
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER_ADDR, &[]),
+            add_hook("overflow".to_string()),
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::TooManyHooks { max: MAX_HOOKS });
+}
+
+#[test]
+fn approving_revoking() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // Mint a token
+    let token_id = "grow".to_string();
+    let token_uri = "https://www.merriam-webster.com/dictionary/grow".to_string();
+
+    let mint_msg = Cw721ExecuteMsg::Mint {
+        token_id: token_id.clone(),
+        owner: String::from("demeter"),
+        token_uri: Some(token_uri),
+        extension: None,
+    };
+
+    let minter = mock_info(MINTER_ADDR, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    // token owner shows in approval query
+    let res = contract
+        .query_approval(
+            deps.as_ref(),
+            mock_env(),
+            token_id.clone(),
+            String::from("demeter"),
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        ApprovalResponse {
+            approval: Approval {
+                spender: Addr::unchecked("demeter"),
+                expires: Expiration::Never {}
+            }
+        }
+    );
+
+    // Give random transferring power
+    let approve_msg = Cw721ExecuteMsg::Approve {
+        spender: String::from("random"),
+        token_id: token_id.clone(),
+        expires: None,
+    };
+    let owner = mock_info("demeter", &[]);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), owner, approve_msg)
+        .unwrap();
+    assert_eq!(
+        res,
+        Response::new()
+            .add_attribute("action", "approve")
+            .add_attribute("sender", "demeter")
+            .add_attribute("spender", "random")
+            .add_attribute("token_id", token_id.clone())
+    );
+
+    // test approval query
+    let res = contract
+        .query_approval(
+            deps.as_ref(),
+            mock_env(),
+            token_id.clone(),
+            String::from("random"),
+            true,
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        ApprovalResponse {
+            approval: Approval {
+                spender: Addr::unchecked("random"),
+                expires: Expiration::Never {}
+            }
+        }
+    );
+
+    // random can now transfer
+    let random = mock_info("random", &[]);
+    let transfer_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: String::from("person"),
+        token_id: token_id.clone(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), random, transfer_msg)
+        .unwrap();
+
+    // Approvals are removed / cleared
+    let query_msg = Cw721QueryMsg::OwnerOf {
+        token_id: token_id.clone(),
+        include_expired: None,
+    };
+    let res: OwnerOfResponse = from_json(
+        contract
+            .query(deps.as_ref(), mock_env(), query_msg.clone())
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        OwnerOfResponse {
+            owner: String::from("person"),
+            approvals: vec![],
+        }
+    );
+
+    // Approve, revoke, and check for empty, to test revoke
+    let approve_msg = Cw721ExecuteMsg::Approve {
+        spender: String::from("random"),
+        token_id: token_id.clone(),
+        expires: None,
+    };
+    let owner = mock_info("person", &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), approve_msg)
+        .unwrap();
+
+    let revoke_msg = Cw721ExecuteMsg::Revoke {
+        spender: String::from("random"),
+        token_id,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, revoke_msg)
+        .unwrap();
+
+    // Approvals are now removed / cleared
+    let res: OwnerOfResponse = from_json(
+        contract
+            .query(deps.as_ref(), mock_env(), query_msg)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        OwnerOfResponse {
+            owner: String::from("person"),
+            approvals: vec![],
+        }
+    );
+}
+
+#[test]
+fn approving_all_revoking_all() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    // Mint a couple tokens (from the same owner)
+    let token_id1 = "grow1".to_string();
+    let token_uri1 = "https://www.merriam-webster.com/dictionary/grow1".to_string();
+
+    let token_id2 = "grow2".to_string();
+    let token_uri2 = "https://www.merriam-webster.com/dictionary/grow2".to_string();
+
+    let mint_msg1 = Cw721ExecuteMsg::Mint {
+        token_id: token_id1.clone(),
+        owner: String::from("demeter"),
+        token_uri: Some(token_uri1),
+        extension: None,
+    };
+
+    let minter = mock_info(MINTER_ADDR, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg1)
+        .unwrap();
+
+    let mint_msg2 = Cw721ExecuteMsg::Mint {
         token_id: token_id2.clone(),
         owner: String::from("demeter"),
         token_uri: Some(token_uri2),
@@ -942,6 +2210,405 @@ fn approving_all_revoking_all() {
     }
 }
 
+#[test]
+fn permit() {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApiBech32::new("cosmwasm"),
+        querier: MockQuerier::<Empty>::new(&[]),
+        custom_query_type: PhantomData,
+    };
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    let minter = deps.api.addr_make(MINTER_ADDR);
+    let spender = deps.api.addr_make("spender");
+    let operator = deps.api.addr_make("operator");
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721InstantiateMsg {
+                name: CONTRACT_NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+                minter: Some(minter.to_string()),
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+
+    // owner is derived from the public key
+    let signing_key = SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+    let pub_key = Binary::from(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes(),
+    );
+    let owner = derive_address(&deps.api, &pub_key).unwrap();
+    // payload is signed as ADR-036 arbitrary data
+    let sign = |payload: &PermitPayload| {
+        let sign_doc = format!(
+            r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"memo":"","msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","signer":"{}"}}}}],"sequence":"0"}}"#,
+            to_json_binary(payload).unwrap().to_base64(),
+            owner
+        );
+        let hash = Sha256::digest(sign_doc.as_bytes());
+        let signature: Signature = signing_key.sign_prehash(&hash).unwrap();
+        Binary::from(signature.to_bytes().as_slice())
+    };
+
+    let token_id = "grow".to_string();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(minter.as_str(), &[]),
+            Cw721ExecuteMsg::Mint {
+                token_id: token_id.clone(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+
+    let env = mock_env();
+    let relayer = mock_info("relayer", &[]);
+    let payload = PermitPayload {
+        token_id: Some(token_id.clone()),
+        spender: spender.to_string(),
+        expires: None,
+        nonce: 0,
+        chain_id: env.block.chain_id.clone(),
+        contract_address: env.contract.address.to_string(),
+    };
+
+    // signature must match the payload
+    let other_payload = PermitPayload {
+        spender: operator.to_string(),
+        ..payload.clone()
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            Cw721ExecuteMsg::Permit {
+                payload: payload.clone(),
+                pub_key: pub_key.clone(),
+                signature: sign(&other_payload),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::InvalidPermitSignature {});
+
+    // permits of other chains are rejected
+    let other_payload = PermitPayload {
+        chain_id: "other-chain".to_string(),
+        ..payload.clone()
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            Cw721ExecuteMsg::Permit {
+                payload: other_payload.clone(),
+                pub_key: pub_key.clone(),
+                signature: sign(&other_payload),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::PermitMismatch {});
+
+    // relayer applies the approval on behalf of the owner
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            Cw721ExecuteMsg::Permit {
+                payload: payload.clone(),
+                pub_key: pub_key.clone(),
+                signature: sign(&payload),
+            },
+        )
+        .unwrap();
+    contract
+        .query_approval(
+            deps.as_ref(),
+            env.clone(),
+            token_id.clone(),
+            spender.to_string(),
+            false,
+        )
+        .unwrap();
+
+    // permit can't be replayed
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            Cw721ExecuteMsg::Permit {
+                payload: payload.clone(),
+                pub_key: pub_key.clone(),
+                signature: sign(&payload),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::InvalidPermitNonce { expected: 1 });
+    let res = contract
+        .query_permit_nonce(deps.as_ref(), owner.to_string())
+        .unwrap();
+    assert_eq!(res, PermitNonceResponse { nonce: 1 });
+
+    // without token id, the spender is approved as operator
+    let payload = PermitPayload {
+        token_id: None,
+        spender: operator.to_string(),
+        nonce: 1,
+        ..payload
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            relayer.clone(),
+            Cw721ExecuteMsg::Permit {
+                payload: payload.clone(),
+                pub_key: pub_key.clone(),
+                signature: sign(&payload),
+            },
+        )
+        .unwrap();
+    let res = contract
+        .query_operator(
+            deps.as_ref(),
+            env.clone(),
+            owner.to_string(),
+            operator.to_string(),
+            false,
+        )
+        .unwrap();
+    assert_eq!(res.approval.spender, operator);
+
+    // owner revokes unused permits up to nonce 3
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            Cw721ExecuteMsg::RevokePermit { nonce: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::InvalidPermitNonce { expected: 2 });
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner.as_str(), &[]),
+            Cw721ExecuteMsg::RevokePermit { nonce: 3 },
+        )
+        .unwrap();
+    let payload = PermitPayload {
+        nonce: 3,
+        ..payload
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            relayer,
+            Cw721ExecuteMsg::Permit {
+                payload: payload.clone(),
+                pub_key,
+                signature: sign(&payload),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::InvalidPermitNonce { expected: 4 });
+}
+
+#[test]
+fn mint_voucher() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+    let env = mock_env();
+    let minter = mock_info(MINTER_ADDR, &[]);
+    let buyer = mock_info("buyer", &coins(100, "uatom"));
+
+    let signing_key = SigningKey::from_bytes(&[2u8; 32].into()).unwrap();
+    let pub_key = Binary::from(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes(),
+    );
+    let sign = |voucher: &MintVoucher| {
+        let hash = Sha256::digest(to_json_vec(voucher).unwrap());
+        let signature: Signature = signing_key.sign_prehash(&hash).unwrap();
+        Binary::from(signature.to_bytes().as_slice())
+    };
+
+    let extension = Some(Metadata {
+        name: Some("Grow".to_string()),
+        ..Metadata::default()
+    });
+    let voucher = MintVoucher {
+        token_id: "grow".to_string(),
+        token_uri: Some("https://www.merriam-webster.com/dictionary/grow".to_string()),
+        extension_hash: Binary::from(Sha256::digest(to_json_vec(&extension).unwrap()).as_slice()),
+        price: coin(100, "uatom"),
+        expires: Expiration::AtHeight(env.block.height + 10),
+        nonce: 1,
+        chain_id: env.block.chain_id.clone(),
+        contract_address: env.contract.address.to_string(),
+    };
+    let redeem_msg = Cw721ExecuteMsg::RedeemVoucher {
+        voucher: voucher.clone(),
+        extension: extension.clone(),
+        signature: sign(&voucher),
+    };
+
+    // vouchers require a signer
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            buyer.clone(),
+            redeem_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::NoVoucherSigner {});
+
+    // only minter can set the signer
+    let set_signer_msg = Cw721ExecuteMsg::SetVoucherSigner {
+        pub_key: Some(pub_key.clone()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            buyer.clone(),
+            set_signer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::Ownership(OwnershipError::NotOwner));
+    // uncompressed or malformed keys are rejected
+    let uncompressed_key = Binary::from(
+        signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes(),
+    );
+    let mut bad_prefix_key = pub_key.to_vec();
+    bad_prefix_key[0] = 0x04;
+    for invalid_key in [uncompressed_key, Binary::from(bad_prefix_key)] {
+        let err = contract
+            .execute(
+                deps.as_mut(),
+                env.clone(),
+                minter.clone(),
+                Cw721ExecuteMsg::SetVoucherSigner {
+                    pub_key: Some(invalid_key),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err, Cw721ContractError::InvalidVoucherSigner {});
+    }
+    contract
+        .execute(deps.as_mut(), env.clone(), minter, set_signer_msg)
+        .unwrap();
+    let signer = contract.query_voucher_signer(deps.as_ref()).unwrap();
+    assert_eq!(signer, Some(pub_key));
+
+    // price must be paid
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(50, "uatom")),
+            redeem_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::InvalidVoucherPayment {
+            expected: coin(100, "uatom")
+        }
+    );
+
+    // extension must match the signed hash
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            buyer.clone(),
+            Cw721ExecuteMsg::RedeemVoucher {
+                voucher: voucher.clone(),
+                extension: None,
+                signature: sign(&voucher),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::VoucherExtensionMismatch {});
+
+    // voucher can't be altered
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            buyer.clone(),
+            Cw721ExecuteMsg::RedeemVoucher {
+                voucher: MintVoucher {
+                    price: coin(1, "uatom"),
+                    ..voucher.clone()
+                },
+                extension: extension.clone(),
+                signature: sign(&voucher),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::InvalidVoucherSignature {});
+
+    // expired vouchers are rejected
+    let mut late_env = env.clone();
+    late_env.block.height += 10;
+    let err = contract
+        .execute(deps.as_mut(), late_env, buyer.clone(), redeem_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::VoucherExpired {});
+
+    // buyer redeems the voucher
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            buyer.clone(),
+            redeem_msg.clone(),
+        )
+        .unwrap();
+    let res = contract
+        .query_all_nft_info(deps.as_ref(), env.clone(), "grow".to_string(), false)
+        .unwrap();
+    assert_eq!(res.access.owner, "buyer");
+    assert_eq!(res.info.token_uri, voucher.token_uri);
+    assert_eq!(res.info.extension, extension);
+    assert!(contract.query_voucher_redeemed(deps.as_ref(), 1).unwrap());
+
+    // voucher can't be redeemed twice
+    let err = contract
+        .execute(deps.as_mut(), env, buyer, redeem_msg)
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::VoucherRedeemed { nonce: 1 });
+}
+
 #[test]
 fn test_set_withdraw_address() {
     let mut deps = mock_dependencies();
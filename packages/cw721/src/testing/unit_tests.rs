@@ -1,12 +1,18 @@
 use crate::{
-    execute::Cw721Execute,
-    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg},
+    error::Cw721ContractError,
+    execute::{validate_metadata_extension, Cw721Execute},
+    msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg, NumTokensResponse},
     query::{Cw721Query, MAX_LIMIT},
-    state::{CollectionInfo, DefaultOptionMetadataExtension, Metadata, MINTER},
+    state::{
+        CollectionInfo, ColorFormat, DefaultOptionMetadataExtension, Metadata, MetadataValidation,
+        Trait, MINTER,
+    },
 };
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
+    from_json,
     testing::{mock_dependencies, mock_env, mock_info},
-    Addr, Empty,
+    to_json_binary, Addr, Binary, Deps, Empty, Env, StdError, StdResult,
 };
 use cw2::ContractVersion;
 use cw_storage_plus::Item;
@@ -19,7 +25,7 @@ use super::*;
 fn proper_cw2_initialization() {
     let mut deps = mock_dependencies();
 
-    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default()
+    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default()
         .instantiate(
             deps.as_mut(),
             mock_env(),
@@ -29,6 +35,9 @@ fn proper_cw2_initialization() {
                 symbol: "collection_symbol".into(),
                 minter: Some("minter".into()),
                 withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
             },
             "contract_name",
             "contract_version",
@@ -57,7 +66,7 @@ fn proper_owner_initialization() {
     let mut deps = mock_dependencies();
 
     let info_owner = mock_info("owner", &[]);
-    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default()
+    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default()
         .instantiate(
             deps.as_mut(),
             mock_env(),
@@ -67,6 +76,9 @@ fn proper_owner_initialization() {
                 symbol: "collection_symbol".into(),
                 minter: None,
                 withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
             },
             "contract_name",
             "contract_version",
@@ -80,7 +92,7 @@ fn proper_owner_initialization() {
 #[test]
 fn use_metadata_extension() {
     let mut deps = mock_dependencies();
-    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
 
     let info = mock_info(CREATOR_ADDR, &[]);
     let init_msg = Cw721InstantiateMsg {
@@ -88,6 +100,9 @@ fn use_metadata_extension() {
         symbol: "collection_symbol".into(),
         minter: None,
         withdraw_address: None,
+        snapshot_ownership: None,
+        clawback_enabled: None,
+        clear_user_on_transfer: None,
     };
     let env = mock_env();
     contract
@@ -125,6 +140,493 @@ fn use_metadata_extension() {
     assert_eq!(res.extension, extension);
 }
 
+#[test]
+fn validate_metadata() {
+    let rules = MetadataValidation::default();
+    let valid = Metadata {
+        image: Some("ipfs://QmYjtig7VJQ6XsnUjqqJvj7QaMcCAwtrgNdahSiFofrE7o".into()),
+        external_url: Some("https://starships.example.com".into()),
+        animation_url: Some("ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U".into()),
+        background_color: Some("1a2b3c".into()),
+        attributes: Some(vec![Trait {
+            display_type: None,
+            trait_type: "warp_speed".into(),
+            value: "9".into(),
+        }]),
+        ..Metadata::default()
+    };
+    valid.validate(&rules).unwrap();
+
+    // empty trait type
+    let err = Metadata {
+        attributes: Some(vec![Trait {
+            display_type: None,
+            trait_type: " ".into(),
+            value: "9".into(),
+        }]),
+        ..valid.clone()
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(err, Cw721ContractError::EmptyTraitType {});
+
+    // too many attributes
+    let err = Metadata {
+        attributes: Some(vec![
+            Trait {
+                display_type: None,
+                trait_type: "warp_speed".into(),
+                value: "9".into(),
+            };
+            rules.max_attributes as usize + 1
+        ]),
+        ..valid.clone()
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::TooManyAttributes {
+            max: rules.max_attributes
+        }
+    );
+
+    // non-hex background color
+    let err = Metadata {
+        background_color: Some("#1a2b3c".into()),
+        ..valid.clone()
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::InvalidBackgroundColor {
+            color: "#1a2b3c".into()
+        }
+    );
+    Metadata {
+        background_color: Some("#1a2b3c".into()),
+        ..valid.clone()
+    }
+    .validate(&MetadataValidation {
+        background_color_format: ColorFormat::HashHex,
+        ..rules.clone()
+    })
+    .unwrap();
+
+    // youtube url is not a url
+    let err = Metadata {
+        youtube_url: Some("not a url".into()),
+        ..valid.clone()
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::InvalidUrl {
+            field: "youtube_url".into(),
+            url: "not a url".into()
+        }
+    );
+
+    // scheme not allowed
+    let err = Metadata {
+        image: Some("http://starships.example.com/enterprise.png".into()),
+        ..valid.clone()
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::UrlSchemeNotAllowed {
+            field: "image".into(),
+            scheme: "http".into()
+        }
+    );
+
+    // image data too large
+    let err = Metadata {
+        image_data: Some("a".repeat(rules.max_image_data_length as usize + 1)),
+        ..valid
+    }
+    .validate(&rules)
+    .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::MetadataFieldTooLong {
+            field: "image_data".into(),
+            max: rules.max_image_data_length
+        }
+    );
+}
+
+#[test]
+fn update_metadata_validation() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721InstantiateMsg {
+                name: "collection_name".into(),
+                symbol: "collection_symbol".into(),
+                minter: None,
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+
+    // defaults are used if not set
+    let rules = contract.query_metadata_validation(deps.as_ref()).unwrap();
+    assert_eq!(rules, MetadataValidation::default());
+    let extension = Some(Metadata {
+        image: Some("http://starships.example.com/enterprise.png".into()),
+        ..Metadata::default()
+    });
+    validate_metadata_extension(deps.as_ref().storage, &extension).unwrap_err();
+    validate_metadata_extension(deps.as_ref().storage, &None).unwrap();
+
+    // only owner can update
+    let rules = MetadataValidation {
+        allowed_url_schemes: vec!["http".into()],
+        ..MetadataValidation::default()
+    };
+    let exec_msg = Cw721ExecuteMsg::UpdateMetadataValidation {
+        validation: rules.clone(),
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("random", &[]),
+            exec_msg.clone(),
+        )
+        .unwrap_err();
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            exec_msg,
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.query_metadata_validation(deps.as_ref()).unwrap(),
+        rules
+    );
+    validate_metadata_extension(deps.as_ref().storage, &extension).unwrap();
+
+    // zero limits are rejected
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721ExecuteMsg::UpdateMetadataValidation {
+                validation: MetadataValidation {
+                    max_attributes: 0,
+                    ..MetadataValidation::default()
+                },
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::InvalidMetadataValidation {
+            reason: "max_attributes must be greater than zero".to_string()
+        }
+    );
+
+    // schemes that can never match are rejected
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721ExecuteMsg::UpdateMetadataValidation {
+                validation: MetadataValidation {
+                    allowed_url_schemes: vec!["https://".into()],
+                    ..MetadataValidation::default()
+                },
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::InvalidMetadataValidation {
+            reason: "invalid url scheme: https://".to_string()
+        }
+    );
+    assert_eq!(
+        contract.query_metadata_validation(deps.as_ref()).unwrap(),
+        rules
+    );
+}
+
+#[test]
+fn mint_validates_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721InstantiateMsg {
+                name: "collection_name".into(),
+                symbol: "collection_symbol".into(),
+                minter: None,
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+
+    // library consumers calling the trait directly are validated as well
+    let err = contract
+        .mint(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            "1".into(),
+            "john".into(),
+            None,
+            Some(Metadata {
+                image: Some("http://starships.example.com/enterprise.png".into()),
+                ..Metadata::default()
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::UrlSchemeNotAllowed {
+            field: "image".into(),
+            scheme: "http".into()
+        }
+    );
+
+    // extensions other than the default metadata are accepted
+    Cw721Contract::<Empty, Empty, Empty, Empty>::default()
+        .mint(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            "1".into(),
+            "john".into(),
+            None,
+            Empty {},
+        )
+        .unwrap();
+
+    // also if their fields overlap with the default metadata
+    Cw721Contract::<Option<CustomMetadata>, Empty, Empty, Empty>::default()
+        .mint(
+            deps.as_mut(),
+            env,
+            mock_info(CREATOR_ADDR, &[]),
+            "2".into(),
+            "john".into(),
+            None,
+            Some(CustomMetadata {
+                image: Some("http://starships.example.com/enterprise.png".into()),
+            }),
+        )
+        .unwrap();
+}
+
+#[cw_serde]
+struct CustomMetadata {
+    image: Option<String>,
+}
+
+#[test]
+fn update_nft_info_validates_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    let env = mock_env();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721InstantiateMsg {
+                name: "collection_name".into(),
+                symbol: "collection_symbol".into(),
+                minter: None,
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+    contract
+        .mint(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            "1".into(),
+            "john".into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    let update_msg = |image: &str| Cw721ExecuteMsg::UpdateNftInfo {
+        token_id: "1".into(),
+        token_uri: Some("ipfs://token/1".into()),
+        extension: Some(Metadata {
+            image: Some(image.into()),
+            ..Metadata::default()
+        }),
+    };
+
+    // only minter can update
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("john", &[]),
+            update_msg("ipfs://image/1"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+    );
+
+    // invalid metadata is rejected
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            update_msg("http://starships.example.com/enterprise.png"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Cw721ContractError::UrlSchemeNotAllowed {
+            field: "image".into(),
+            scheme: "http".into()
+        }
+    );
+
+    contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CREATOR_ADDR, &[]),
+            update_msg("ipfs://image/1"),
+        )
+        .unwrap();
+    let info = contract
+        .query_nft_info(deps.as_ref(), env, "1".into())
+        .unwrap();
+    assert_eq!(info.token_uri, Some("ipfs://token/1".into()));
+    assert_eq!(
+        info.extension,
+        Some(Metadata {
+            image: Some("ipfs://image/1".into()),
+            ..Metadata::default()
+        })
+    );
+}
+
+#[cw_serde]
+enum CustomQueryMsg {
+    Echo { text: String },
+}
+
+struct CustomQueryContract;
+
+impl Cw721Query<DefaultOptionMetadataExtension, CustomQueryMsg> for CustomQueryContract {
+    fn query_extension(&self, _deps: Deps, _env: Env, msg: CustomQueryMsg) -> StdResult<Binary> {
+        match msg {
+            CustomQueryMsg::Echo { text } => to_json_binary(&text),
+        }
+    }
+}
+
+#[test]
+fn query_extension() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
+    contract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CREATOR_ADDR, &[]),
+            Cw721InstantiateMsg {
+                name: "collection_name".into(),
+                symbol: "collection_symbol".into(),
+                minter: None,
+                withdraw_address: None,
+                snapshot_ownership: None,
+                clawback_enabled: None,
+                clear_user_on_transfer: None,
+            },
+            "contract_name",
+            "contract_version",
+        )
+        .unwrap();
+
+    // default implementation rejects extension queries
+    let err = contract
+        .query(
+            deps.as_ref(),
+            mock_env(),
+            Cw721QueryMsg::Extension { msg: Empty {} },
+        )
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("extension query not supported"));
+
+    // custom query is dispatched to the overridden hook
+    let res = CustomQueryContract
+        .query(
+            deps.as_ref(),
+            mock_env(),
+            Cw721QueryMsg::Extension {
+                msg: CustomQueryMsg::Echo {
+                    text: "hello".to_string(),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(from_json::<String>(res).unwrap(), "hello");
+
+    // standard queries are still served
+    let res = CustomQueryContract
+        .query(deps.as_ref(), mock_env(), Cw721QueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(from_json::<NumTokensResponse>(res).unwrap().count, 0);
+
+    // dummy query is rejected
+    CustomQueryContract
+        .query(
+            deps.as_ref(),
+            mock_env(),
+            Cw721QueryMsg::GetMetadataExtension { msg: None },
+        )
+        .unwrap_err();
+}
+
 #[test]
 fn test_migrate() {
     let mut deps = mock_dependencies();
@@ -158,7 +660,7 @@ fn test_migrate() {
     // assert new data before migration:
     // - ownership and collection info throws NotFound Error
     MINTER.item.load(deps.as_ref().storage).unwrap_err(); // cw_ownable in v16 is used for minter
-    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default();
+    let contract = Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default();
     contract
         .query_collection_info(deps.as_ref(), env.clone())
         .unwrap_err();
@@ -187,7 +689,7 @@ fn test_migrate() {
         assert_eq!(token.owner.as_str(), "owner");
     }
 
-    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty>::default()
+    Cw721Contract::<DefaultOptionMetadataExtension, Empty, Empty, Empty>::default()
         .migrate(
             deps.as_mut(),
             env.clone(),
@@ -252,4 +754,72 @@ fn test_migrate() {
             .unwrap();
         assert_eq!(token.owner.as_str(), "owner");
     }
+
+    // - collection stats: first page of balances is counted on migrate
+    let stats = contract
+        .query_collection_stats(deps.as_ref(), env.clone())
+        .unwrap();
+    assert_eq!(stats.total_minted, 200);
+    assert_eq!(stats.total_burned, 0);
+    assert_eq!(stats.num_holders, 1);
+    assert!(stats.migration_pending);
+    let balance = |deps: Deps, owner: &str| {
+        contract
+            .query_balance_of(deps, mock_env(), owner.to_string())
+            .unwrap()
+            .balance
+    };
+    assert_eq!(balance(deps.as_ref(), "owner"), 100);
+
+    // transfers of counted tokens update balances, others are counted later
+    for token_id in ["0", "99"] {
+        contract
+            .execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("owner", &[]),
+                Cw721ExecuteMsg::TransferNft {
+                    recipient: "other".to_string(),
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+    }
+    assert_eq!(balance(deps.as_ref(), "owner"), 99);
+    assert_eq!(balance(deps.as_ref(), "other"), 1);
+
+    // anyone can continue the migration
+    let res = contract
+        .execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            Cw721ExecuteMsg::MigrateCollectionStats { limit: None },
+        )
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "migrate_collection_stats"),
+            ("counted", "100"),
+            ("done", "true")
+        ]
+    );
+    assert_eq!(balance(deps.as_ref(), "owner"), 198);
+    assert_eq!(balance(deps.as_ref(), "other"), 2);
+    let stats = contract
+        .query_collection_stats(deps.as_ref(), env.clone())
+        .unwrap();
+    assert_eq!(stats.num_holders, 2);
+    assert!(!stats.migration_pending);
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            env,
+            mock_info("anyone", &[]),
+            Cw721ExecuteMsg::MigrateCollectionStats { limit: None },
+        )
+        .unwrap_err();
+    assert_eq!(err, Cw721ContractError::NoCollectionStatsMigration {});
 }
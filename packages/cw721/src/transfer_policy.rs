@@ -0,0 +1,24 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+
+/// Query interface a transfer policy contract must implement.
+/// The cw721 contract asks it on every transfer and send whether the transfer is allowed.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum TransferPolicyQueryMsg {
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_id: String,
+        /// Current owner of the token
+        owner: String,
+        /// Sender of the transfer, either owner, approved spender or operator
+        sender: String,
+        recipient: String,
+    },
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub allowed: bool,
+    /// Optional reason, in case transfer is not allowed
+    pub reason: Option<String>,
+}